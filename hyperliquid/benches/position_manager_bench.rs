@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tokio::sync::RwLock;
+
+use hyperliquid_market_maker::{Position, PositionManager, RedisPool};
+
+/// Build a synthetic position map of `count` distinct symbols so the
+/// publish path can be exercised without a live exchange feed.
+fn synthetic_positions(count: usize) -> HashMap<String, Position> {
+    (0..count)
+        .map(|i| {
+            let symbol = format!("SYM{i}");
+            (
+                symbol.clone(),
+                Position {
+                    symbol,
+                    size: 10.0,
+                    entry_price: 100.0,
+                    current_price: 101.0,
+                    unrealized_pnl: 10.0,
+                    notional_usd: 1010.0,
+                    version: 1,
+                    last_update_ts: 0,
+                    realized_pnl: 0.0,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Requires a reachable Redis instance at `REDIS_URL` (defaults to
+/// `redis://127.0.0.1/`), matching how the rest of the crate picks up Redis
+/// connectivity. Run with `cargo bench --bench position_manager_bench`.
+fn bench_publish_position_updates(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("build tokio runtime");
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+
+    let redis_pool: RedisPool = runtime.block_on(async {
+        let client = redis::Client::open(redis_url).expect("open redis client");
+        hyperliquid_market_maker::create_redis_pool(
+            client,
+            hyperliquid_market_maker::DEFAULT_POOL_MAX_SIZE,
+            hyperliquid_market_maker::DEFAULT_POOL_CONNECTION_TIMEOUT,
+        )
+        .await
+        .expect("create redis pool")
+    });
+
+    let mut group = c.benchmark_group("publish_position_updates");
+    for position_count in [10usize, 100, 1_000, 10_000] {
+        let positions = Arc::new(RwLock::new(synthetic_positions(position_count)));
+        let manager = PositionManager::new(
+            redis_pool.clone(),
+            positions,
+            Duration::from_secs(1),
+            "mm_position_updates_bench".to_string(),
+            Vec::new(),
+            Arc::new(RwLock::new(HashMap::new())),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(position_count),
+            &position_count,
+            |b, _| {
+                b.to_async(&runtime).iter(|| async {
+                    black_box(manager.publish_position_updates().await.expect("publish"));
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_publish_position_updates);
+criterion_main!(benches);
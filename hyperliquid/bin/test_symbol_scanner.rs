@@ -7,7 +7,7 @@ use redis::Client as RedisClient;
 use log::{info, error, warn, debug};
 
 // Import the SymbolScanner from the main crate
-use hyperliquid_market_maker::SymbolScanner;
+use hyperliquid_market_maker::{SymbolScanner, create_redis_pool, ResilientPubSub, DEFAULT_POOL_MAX_SIZE, DEFAULT_POOL_CONNECTION_TIMEOUT};
 
 #[tokio::main]
 async fn main() {
@@ -44,10 +44,19 @@ async fn main() {
     
     // Create shared state for metrics
     let symbol_metrics = Arc::new(RwLock::new(HashMap::new()));
-    
+
+    // Pool connections so high-frequency candle writes reuse warm connections
+    let redis_pool = match create_redis_pool(redis_client, DEFAULT_POOL_MAX_SIZE, DEFAULT_POOL_CONNECTION_TIMEOUT).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to create Redis pool: {}", e);
+            return;
+        }
+    };
+
     // Create and start the symbol scanner
     match SymbolScanner::new(
-        redis_client,
+        redis_pool,
         symbol_metrics.clone(),
         Duration::from_secs(3600), // 1 hour scan interval
         10, // Top 10 symbols
@@ -65,38 +74,16 @@ async fn main() {
                 }
             };
             
-            // Create a task to monitor Redis updates
-            let metrics_clone = symbol_metrics.clone();
+            // Create a task to monitor Redis updates. The subscription
+            // auto-reconnects on a dropped stream and buffers incoming
+            // payloads so this monitor task can't stall message intake.
             tokio::spawn(async move {
-                // Subscribe to Redis updates
-                match redis_client_sub.get_async_connection().await {
-                    Ok(conn) => {
-                        let mut pubsub = conn.into_pubsub();
-                        
-                        // Subscribe to position updates channel
-                        if let Err(e) = pubsub.subscribe("mm_position_updates").await {
-                            error!("Failed to subscribe to Redis channel: {}", e);
-                            return;
-                        }
-                        
-                        info!("Subscribed to Redis channel 'mm_position_updates'");
-                        
-                        let mut stream = pubsub.on_message();
-                        while let Some(msg) = stream.next().await {
-                            let payload: String = match msg.get_payload() {
-                                Ok(payload) => payload,
-                                Err(e) => {
-                                    error!("Failed to get message payload: {}", e);
-                                    continue;
-                                }
-                            };
-                            
-                            info!("Received Redis message: {}", payload);
-                        }
-                    },
-                    Err(e) => {
-                        error!("Failed to get Redis connection for PubSub: {}", e);
-                    }
+                let pubsub = ResilientPubSub::subscribe(redis_client_sub, "mm_position_updates".to_string());
+                info!("Subscribed to Redis channel 'mm_position_updates'");
+
+                loop {
+                    let payload = pubsub.recv().await;
+                    info!("Received Redis message: {}", payload);
                 }
             });
             
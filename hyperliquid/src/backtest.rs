@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use log::info;
+
+use crate::enhanced_market_maker::{EnhancedRestingOrder, MarketMakerConfig, Position};
+
+/// Hard cap on resting limit orders per side in the simulator, mirroring the
+/// bounded order book real exchanges enforce to prevent unbounded quoting.
+pub const MAX_NUM_LIMIT_ORDERS: usize = 10;
+
+/// One tick of replayed mid-price data, standing in for a live
+/// `Message::AllMids` update while backtesting offline.
+#[derive(Clone, Debug)]
+pub struct ReplayTick {
+    pub symbol: String,
+    pub mid_price: f64,
+}
+
+/// Outcome of a completed backtest run
+#[derive(Clone, Debug, Default)]
+pub struct BacktestSummary {
+    pub realized_pnl: f64,
+    pub fill_count: u64,
+    pub final_inventory: f64,
+}
+
+/// Simulates order resting/filling against a replayed stream of mid prices
+/// instead of the live Hyperliquid exchange, so `quote_levels`,
+/// `daily_return_bps`, and the trailing stop/take-profit parameters can be
+/// tuned offline. Mirrors the position/PnL bookkeeping that
+/// `EnhancedMarketMaker::update_position_from_user_state` performs on the
+/// live path, so backtest results are directly comparable.
+pub struct SimulatedExchange {
+    config: MarketMakerConfig,
+    active_orders: HashMap<u64, EnhancedRestingOrder>,
+    next_oid: u64,
+    position: Position,
+    realized_daily_pnl: f64,
+    highest_pnl: f64,
+    lowest_pnl: f64,
+    fill_count: u64,
+}
+
+impl SimulatedExchange {
+    pub fn new(config: MarketMakerConfig) -> Self {
+        let symbol = config.symbol.clone();
+        SimulatedExchange {
+            config,
+            active_orders: HashMap::new(),
+            next_oid: 1,
+            position: Position {
+                symbol,
+                size: 0.0,
+                entry_price: 0.0,
+                current_price: 0.0,
+                unrealized_pnl: 0.0,
+                notional_usd: 0.0,
+                version: 0,
+                last_update_ts: 0,
+                realized_pnl: 0.0,
+            },
+            realized_daily_pnl: 0.0,
+            highest_pnl: 0.0,
+            lowest_pnl: 0.0,
+            fill_count: 0,
+        }
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn active_orders(&self) -> &HashMap<u64, EnhancedRestingOrder> {
+        &self.active_orders
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.config.symbol
+    }
+
+    /// Validate a desired order against the simulator's bounded book and the
+    /// configured `max_long_usd`/`max_short_usd` exposure limits before it
+    /// is allowed to rest.
+    pub fn validate_order(&self, is_bid: bool, price: f64, size: f64) -> Result<(), String> {
+        let same_side_count = self.active_orders.values().filter(|o| o.is_bid == is_bid).count();
+        if same_side_count >= MAX_NUM_LIMIT_ORDERS {
+            return Err(format!(
+                "rejected {} order: {} orders already resting on that side (max {})",
+                if is_bid { "bid" } else { "ask" }, same_side_count, MAX_NUM_LIMIT_ORDERS
+            ));
+        }
+
+        let projected_size = if is_bid { self.position.size + size } else { self.position.size - size };
+        let projected_notional = price * projected_size.abs();
+
+        if is_bid && projected_size > 0.0 && projected_notional > self.config.max_long_usd {
+            return Err(format!(
+                "rejected bid order: projected long notional {} exceeds max_long_usd {}",
+                projected_notional, self.config.max_long_usd
+            ));
+        }
+        if !is_bid && projected_size < 0.0 && projected_notional > self.config.max_short_usd {
+            return Err(format!(
+                "rejected ask order: projected short notional {} exceeds max_short_usd {}",
+                projected_notional, self.config.max_short_usd
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Place a validated order into the simulated book, returning its oid.
+    pub fn place_order(&mut self, is_bid: bool, level: u16, price: f64, size: f64) -> Result<u64, String> {
+        self.validate_order(is_bid, price, size)?;
+        let oid = self.next_oid;
+        self.next_oid += 1;
+        self.active_orders.insert(oid, EnhancedRestingOrder {
+            oid, position: size, price, is_bid, level, trigger_kind: None,
+            original_size: size, filled_size: 0.0,
+        });
+        Ok(oid)
+    }
+
+    pub fn cancel_order(&mut self, oid: u64) {
+        self.active_orders.remove(&oid);
+    }
+
+    /// Feed one replayed mid price through the simulator: any resting order
+    /// the price crosses is filled, updating `position`, `realized_daily_pnl`,
+    /// `highest_pnl`, and `lowest_pnl` exactly as the live path would.
+    pub fn process_tick(&mut self, tick: &ReplayTick) {
+        if tick.symbol != self.config.symbol {
+            return;
+        }
+
+        let crossed: Vec<u64> = self.active_orders.iter()
+            .filter(|(_, order)| {
+                if order.is_bid { tick.mid_price <= order.price } else { tick.mid_price >= order.price }
+            })
+            .map(|(oid, _)| *oid)
+            .collect();
+
+        for oid in crossed {
+            if let Some(order) = self.active_orders.remove(&oid) {
+                self.apply_fill(order.is_bid, order.price, order.position, tick.mid_price);
+            }
+        }
+
+        self.position.current_price = tick.mid_price;
+        self.position.unrealized_pnl = (tick.mid_price - self.position.entry_price) * self.position.size;
+        self.position.notional_usd = tick.mid_price.abs() * self.position.size.abs();
+
+        let total_pnl = self.realized_daily_pnl + self.position.unrealized_pnl;
+        if total_pnl > self.highest_pnl {
+            self.highest_pnl = total_pnl;
+        }
+        if total_pnl < self.lowest_pnl {
+            self.lowest_pnl = total_pnl;
+        }
+    }
+
+    /// Cross any resting order that is marketable against a best bid/ask
+    /// update, as opposed to `process_tick` which crosses against a single
+    /// replayed mid price. Not called from this crate yet - `run_replay`
+    /// only has mid prices to replay - but kept for a real BBO feed, or for
+    /// wiring the simulator into the live quoting loop for paper trading
+    /// (see the `ExchangeBackend` doc comment for why that isn't done yet).
+    /// Returns the oids that filled.
+    pub fn on_bbo_update(&mut self, best_bid: f64, best_ask: f64) -> Vec<u64> {
+        let crossed: Vec<u64> = self.active_orders.iter()
+            .filter(|(_, order)| {
+                if order.is_bid { best_ask <= order.price } else { best_bid >= order.price }
+            })
+            .map(|(oid, _)| *oid)
+            .collect();
+
+        for oid in &crossed {
+            if let Some(order) = self.active_orders.remove(oid) {
+                let fill_price = if order.is_bid { best_ask } else { best_bid };
+                self.apply_fill(order.is_bid, order.price, order.remaining(), fill_price);
+            }
+        }
+
+        let mid = (best_bid + best_ask) / 2.0;
+        self.position.current_price = mid;
+        self.position.unrealized_pnl = (mid - self.position.entry_price) * self.position.size;
+        self.position.notional_usd = mid.abs() * self.position.size.abs();
+
+        crossed
+    }
+
+    /// Simulate an immediate market fill, backing `ExchangeBackend::market_open`
+    /// for this type. Models `slippage` (a fraction of the current mid, e.g.
+    /// 0.03 for 3%) against the trader, the same way `close_all_positions`
+    /// applies slippage against the real exchange.
+    pub fn simulate_market_order(&mut self, is_buy: bool, size: f64, slippage: f64) {
+        let reference_price = self.position.current_price;
+        let fill_price = if is_buy { reference_price * (1.0 + slippage) } else { reference_price * (1.0 - slippage) };
+        self.apply_fill(is_buy, fill_price, size, fill_price);
+    }
+
+    /// Apply a simulated fill using the same volume-weighted average cost
+    /// basis and realized-PnL math as `PositionManager::apply_fill`.
+    fn apply_fill(&mut self, is_bid: bool, fill_price: f64, fill_size: f64, current_price: f64) {
+        let signed_fill_qty = if is_bid { fill_size.abs() } else { -fill_size.abs() };
+        let previous_size = self.position.size;
+
+        let size = if previous_size == 0.0 || previous_size.signum() == signed_fill_qty.signum() {
+            let new_size = previous_size + signed_fill_qty;
+            self.position.entry_price = (self.position.entry_price * previous_size.abs()
+                + fill_price * signed_fill_qty.abs()) / new_size.abs();
+            new_size
+        } else {
+            let closed_qty = signed_fill_qty.abs().min(previous_size.abs());
+            let realized = (fill_price - self.position.entry_price) * closed_qty * previous_size.signum();
+            self.position.realized_pnl += realized;
+            self.realized_daily_pnl += realized;
+
+            let new_size = previous_size + signed_fill_qty;
+            if new_size.abs() < f64::EPSILON {
+                self.position.entry_price = 0.0;
+                0.0
+            } else if new_size.signum() != previous_size.signum() {
+                self.position.entry_price = fill_price;
+                new_size
+            } else {
+                new_size
+            }
+        };
+
+        self.position.size = size;
+        self.position.current_price = current_price;
+        self.fill_count += 1;
+
+        info!(
+            "[BACKTEST] Fill: {} {} @ {}. New size: {}, realized_daily_pnl: {}",
+            if is_bid { "buy" } else { "sell" }, fill_size, fill_price, size, self.realized_daily_pnl
+        );
+    }
+
+    /// Replay an entire stream of mid-price ticks (from a file/Redis) and
+    /// return a summary of realized PnL, fill count, and inventory.
+    pub fn run_replay(&mut self, ticks: impl IntoIterator<Item = ReplayTick>) -> BacktestSummary {
+        for tick in ticks {
+            self.process_tick(&tick);
+        }
+
+        let summary = BacktestSummary {
+            realized_pnl: self.realized_daily_pnl,
+            fill_count: self.fill_count,
+            final_inventory: self.position.size,
+        };
+
+        info!(
+            "[BACKTEST] Replay complete: realized_pnl={}, fill_count={}, final_inventory={}",
+            summary.realized_pnl, summary.fill_count, summary.final_inventory
+        );
+
+        summary
+    }
+}
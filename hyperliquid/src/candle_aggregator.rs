@@ -0,0 +1,221 @@
+use std::collections::{HashMap, VecDeque};
+
+use log::debug;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::candle_backfill::Resolution;
+use crate::redis_pool::RedisPool;
+
+/// How many completed bars of history to retain per resolution, enough to
+/// compute a rolling volume window without re-reading Redis on every tick.
+const MAX_HISTORY: usize = 500;
+
+/// Which stream a candle bar is built from: Hyperliquid's own mid-price
+/// ticks, or this maker's own executed fills. Kept separate so a downstream
+/// dashboard can distinguish market movement from the maker's own flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleSource {
+    Mid,
+    Fill,
+}
+
+impl CandleSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleSource::Mid => "mid",
+            CandleSource::Fill => "fill",
+        }
+    }
+}
+
+/// One OHLCV bar being built live from ticks, completed once a tick lands in
+/// the next bucket.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiveCandle {
+    pub time_open: u64,
+    pub time_close: u64,
+    pub symbol: String,
+    pub interval: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub num_trades: u64,
+}
+
+impl LiveCandle {
+    fn new(symbol: &str, resolution: Resolution, bucket_start: u64, price: f64, size: f64) -> Self {
+        LiveCandle {
+            time_open: bucket_start,
+            time_close: bucket_start + resolution.bucket_ms(),
+            symbol: symbol.to_string(),
+            interval: resolution.as_str().to_string(),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: size.abs(),
+            num_trades: 1,
+        }
+    }
+
+    fn update(&mut self, price: f64, size: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += size.abs();
+        self.num_trades += 1;
+    }
+}
+
+/// Builds live OHLCV bars for one symbol at a fixed set of resolutions, from
+/// either mid-price ticks or fill prints. Completed bars are handed back to
+/// the caller to persist; the still-forming bar for each resolution stays
+/// available via `current_bars`.
+pub struct CandleAggregator {
+    symbol: String,
+    source: CandleSource,
+    resolutions: Vec<Resolution>,
+    current: HashMap<Resolution, LiveCandle>,
+    history: HashMap<Resolution, VecDeque<LiveCandle>>,
+}
+
+impl CandleAggregator {
+    pub fn new(symbol: String, source: CandleSource, resolutions: Vec<Resolution>) -> Self {
+        CandleAggregator {
+            symbol,
+            source,
+            resolutions,
+            current: HashMap::new(),
+            history: HashMap::new(),
+        }
+    }
+
+    pub fn source(&self) -> CandleSource {
+        self.source
+    }
+
+    /// Feed one tick (a mid update, or a fill) at `timestamp_ms`, with
+    /// `size` being 0.0 for a mid tick or the traded quantity for a fill.
+    /// Returns any bars that just completed (their bucket rolled over) so
+    /// the caller can persist them.
+    pub fn ingest(&mut self, timestamp_ms: u64, price: f64, size: f64) -> Vec<LiveCandle> {
+        let mut completed = Vec::new();
+
+        for i in 0..self.resolutions.len() {
+            let resolution = self.resolutions[i];
+            let bucket_ms = resolution.bucket_ms();
+            let bucket_start = (timestamp_ms / bucket_ms) * bucket_ms;
+
+            match self.current.get_mut(&resolution) {
+                Some(candle) if candle.time_open == bucket_start => {
+                    candle.update(price, size);
+                },
+                Some(candle) => {
+                    let finished = candle.clone();
+                    let history = self.history.entry(resolution).or_default();
+                    history.push_back(finished.clone());
+                    if history.len() > MAX_HISTORY {
+                        history.pop_front();
+                    }
+                    completed.push(finished);
+                    self.current.insert(resolution, LiveCandle::new(&self.symbol, resolution, bucket_start, price, size));
+                },
+                None => {
+                    self.current.insert(resolution, LiveCandle::new(&self.symbol, resolution, bucket_start, price, size));
+                }
+            }
+        }
+
+        completed
+    }
+
+    /// The still-forming bar for every tracked resolution
+    pub fn current_bars(&self) -> impl Iterator<Item = &LiveCandle> {
+        self.current.values()
+    }
+
+    /// Sum of volume across the last `lookback` completed bars plus the
+    /// still-forming bar, for `resolution`.
+    pub fn rolling_volume(&self, resolution: Resolution, lookback: usize) -> f64 {
+        let completed_volume: f64 = self.history.get(&resolution)
+            .map(|history| history.iter().rev().take(lookback).map(|c| c.volume).sum())
+            .unwrap_or(0.0);
+        let current_volume = self.current.get(&resolution).map(|c| c.volume).unwrap_or(0.0);
+        completed_volume + current_volume
+    }
+
+    /// Restore the still-forming bar for each resolution from whatever was
+    /// last persisted to Redis, so a short disconnect/restart doesn't leave
+    /// a visible hole in the series.
+    pub async fn backfill_from_redis(&mut self, redis_pool: &RedisPool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = redis_pool.get().await?;
+
+        for i in 0..self.resolutions.len() {
+            let resolution = self.resolutions[i];
+            let key = latest_bar_key(&self.symbol, self.source, resolution);
+            let stored: Option<String> = conn.get(&key).await?;
+            if let Some(json) = stored {
+                if let Ok(candle) = serde_json::from_str::<LiveCandle>(&json) {
+                    debug!("Restored in-progress {} {} candle for {} from Redis",
+                        self.source.as_str(), resolution.as_str(), self.symbol);
+                    self.current.insert(resolution, candle);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Redis key the still-forming bar for `symbol`/`source`/`resolution` is
+/// overwritten under, so a restart can pick up where it left off.
+fn latest_bar_key(symbol: &str, source: CandleSource, resolution: Resolution) -> String {
+    format!("live_candle_latest:{}:{}:{}", source.as_str(), resolution.as_str(), symbol)
+}
+
+/// Redis hash completed bars for `source`/`resolution` are appended into,
+/// keyed by `symbol:time_open` so re-persisting the same bucket overwrites
+/// rather than duplicates, matching the upsert convention `symbol_scanner`
+/// uses for its own candle hashes.
+fn completed_bars_hash_key(source: CandleSource, resolution: Resolution) -> String {
+    format!("live_candles:{}:{}", source.as_str(), resolution.as_str())
+}
+
+/// Persist a completed bar into its per-source/resolution hash. The
+/// latest-bar key used by `backfill_from_redis` is written separately, by
+/// `persist_latest_bar`.
+pub async fn persist_completed_candle(
+    redis_pool: &RedisPool,
+    source: CandleSource,
+    resolution: Resolution,
+    candle: &LiveCandle,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut conn = redis_pool.get().await?;
+    let candle_json = serde_json::to_string(candle)?;
+
+    let hash_key = completed_bars_hash_key(source, resolution);
+    let field = format!("{}:{}", candle.symbol, candle.time_open);
+    conn.hset::<_, _, _, ()>(&hash_key, field, &candle_json).await?;
+
+    Ok(())
+}
+
+/// Overwrite the still-forming bar snapshot used by `backfill_from_redis`,
+/// so the latest bar is queryable by dashboards without scanning the
+/// completed-bars hash.
+pub async fn persist_latest_bar(
+    redis_pool: &RedisPool,
+    source: CandleSource,
+    resolution: Resolution,
+    candle: &LiveCandle,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut conn = redis_pool.get().await?;
+    let candle_json = serde_json::to_string(candle)?;
+    let key = latest_bar_key(&candle.symbol, source, resolution);
+    conn.set::<_, _, ()>(key, &candle_json).await?;
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use crate::symbol_scanner::CandleData;
+
+/// A candle resolution the backfill subsystem can aggregate up to from the
+/// base 1-minute candles fetched from the API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl Resolution {
+    /// Bucket width in milliseconds, used to floor a candle's `time_open`
+    /// into the bucket it aggregates into.
+    pub fn bucket_ms(&self) -> u64 {
+        match self {
+            Resolution::OneMinute => 60_000,
+            Resolution::FiveMinutes => 5 * 60_000,
+            Resolution::FifteenMinutes => 15 * 60_000,
+            Resolution::OneHour => 60 * 60_000,
+            Resolution::OneDay => 24 * 60 * 60_000,
+        }
+    }
+
+    /// The interval string Hyperliquid's API/our Redis keys use.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::OneMinute => "1m",
+            Resolution::FiveMinutes => "5m",
+            Resolution::FifteenMinutes => "15m",
+            Resolution::OneHour => "1h",
+            Resolution::OneDay => "1d",
+        }
+    }
+}
+
+/// The maximum number of 1m candles requested per `candles_snapshot` call,
+/// used to page a long backfill range into several requests.
+pub const MAX_CANDLES_PER_PAGE: u64 = 5000;
+
+/// Split a `[start_time, end_time]` range (in ms) into paged windows sized so
+/// each page requests at most `MAX_CANDLES_PER_PAGE` 1-minute candles.
+pub fn paged_windows(start_time: u64, end_time: u64) -> Vec<(u64, u64)> {
+    let page_span_ms = MAX_CANDLES_PER_PAGE * Resolution::OneMinute.bucket_ms();
+    let mut windows = Vec::new();
+    let mut cursor = start_time;
+
+    while cursor < end_time {
+        let window_end = (cursor + page_span_ms).min(end_time);
+        windows.push((cursor, window_end));
+        cursor = window_end;
+    }
+
+    windows
+}
+
+/// Aggregate a set of base 1-minute candles upward into the given
+/// resolution, keyed by the bucket each candle's `time_open` falls into.
+///
+/// `open`/`close` come from the first/last child candle by `time_open`,
+/// `high`/`low`/`vlm`/`num_trades` are reduced across the bucket, matching
+/// the "aggregate upward" rule: one pass over the base candles produces
+/// every higher resolution without re-fetching from the API.
+pub fn aggregate_candles(base_candles: &[CandleData], resolution: Resolution) -> Vec<CandleData> {
+    let bucket_ms = resolution.bucket_ms();
+    let mut buckets: HashMap<u64, Vec<&CandleData>> = HashMap::new();
+
+    for candle in base_candles {
+        let bucket_start = (candle.time_open / bucket_ms) * bucket_ms;
+        buckets.entry(bucket_start).or_default().push(candle);
+    }
+
+    let mut aggregated: Vec<CandleData> = buckets.into_iter().map(|(bucket_start, mut children)| {
+        children.sort_by_key(|c| c.time_open);
+
+        let first = children.first().expect("bucket always has at least one candle");
+        let last = children.last().expect("bucket always has at least one candle");
+
+        let high = children.iter()
+            .filter_map(|c| c.high.parse::<f64>().ok())
+            .fold(f64::MIN, f64::max);
+        let low = children.iter()
+            .filter_map(|c| c.low.parse::<f64>().ok())
+            .fold(f64::MAX, f64::min);
+        let vlm: f64 = children.iter().filter_map(|c| c.vlm.parse::<f64>().ok()).sum();
+        let num_trades: u64 = children.iter().map(|c| c.num_trades).sum();
+
+        CandleData {
+            time_open: bucket_start,
+            time_close: bucket_start + bucket_ms,
+            coin: first.coin.clone(),
+            candle_interval: resolution.as_str().to_string(),
+            open: first.open.clone(),
+            close: last.close.clone(),
+            high: high.to_string(),
+            low: low.to_string(),
+            vlm: vlm.to_string(),
+            num_trades,
+            volume_24h: first.volume_24h,
+            last_updated: last.last_updated,
+            price: last.price,
+        }
+    }).collect();
+
+    aggregated.sort_by_key(|c| c.time_open);
+    aggregated
+}
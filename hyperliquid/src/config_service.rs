@@ -4,13 +4,50 @@ use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 use log::{info, error, warn};
 use redis::{Client as RedisClient, AsyncCommands};
-use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::redis_pool::RedisPool;
+use crate::metrics;
+use crate::resilient_pubsub::ResilientPubSub;
+use crate::errors::ConfigError;
 
 // Import our enhanced market maker config
 use crate::enhanced_market_maker::MarketMakerConfig;
 
+/// A message published on the config channel: either a full config update
+/// or a tombstone removing a symbol's config entirely. Tried as a tombstone
+/// first since its shape (`symbol` + `deleted`) requires a `deleted` field
+/// that `MarketMakerConfig` does not have, so a real config payload can
+/// never be mistaken for one.
+#[derive(Debug, Clone)]
+pub enum ConfigMessage {
+    Update(MarketMakerConfig),
+    Delete(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteMessage {
+    symbol: String,
+    deleted: bool,
+}
+
+impl ConfigMessage {
+    pub fn parse(payload: &str) -> Result<Self, serde_json::Error> {
+        if let Ok(msg) = serde_json::from_str::<DeleteMessage>(payload) {
+            if msg.deleted {
+                return Ok(ConfigMessage::Delete(msg.symbol));
+            }
+        }
+
+        serde_json::from_str::<MarketMakerConfig>(payload).map(ConfigMessage::Update)
+    }
+}
+
 /// Configuration service that handles dynamic configuration via Redis
 pub struct ConfigService {
+    redis_pool: RedisPool,
+    // Pub/sub needs a dedicated, non-pooled connection, so we keep the raw
+    // client around just for that subscription.
     redis_client: RedisClient,
     configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>,
     config_channel: String,
@@ -19,73 +56,94 @@ pub struct ConfigService {
 impl ConfigService {
     /// Create a new configuration service
     pub fn new(
+        redis_pool: RedisPool,
         redis_client: RedisClient,
         configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>,
         config_channel: String,
     ) -> Self {
         ConfigService {
+            redis_pool,
             redis_client,
             configs,
             config_channel,
         }
     }
-    
-    /// Start the configuration service
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+
+    /// Start the configuration service. Returns once `shutdown` is cancelled.
+    pub async fn start(&self, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Starting Configuration Service");
         info!("Listening for configuration updates on channel: {}", self.config_channel);
-        
-        // Subscribe to Redis channel for configuration updates
-        let mut conn = self.redis_client.get_async_connection().await?;
-        let mut pubsub = conn.into_pubsub();
-        pubsub.subscribe(&self.config_channel).await?;
-        
-        let mut stream = pubsub.on_message();
-        
+
+        // Subscribe to Redis channel for configuration updates. The
+        // subscription auto-reconnects on a dropped stream and buffers
+        // incoming payloads so a slow validate/store cycle below can't
+        // stall intake from Redis.
+        let pubsub = ResilientPubSub::subscribe(self.redis_client.clone(), self.config_channel.clone());
+
         // Process configuration messages
-        while let Some(msg) = stream.next().await {
-            let payload: String = msg.get_payload()?;
+        loop {
+            let payload = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Configuration service shutting down");
+                    return Ok(());
+                }
+                payload = pubsub.recv() => payload,
+            };
             info!("Received configuration update: {}", payload);
-            
+
             // Parse the configuration
-            match serde_json::from_str::<MarketMakerConfig>(&payload) {
-                Ok(config) => {
+            match ConfigMessage::parse(&payload) {
+                Ok(ConfigMessage::Update(config)) => {
                     // Store symbol before moving config
                     let symbol = config.symbol.clone();
-                    
+
                     // Validate configuration
                     if let Err(e) = self.validate_config(&config) {
                         error!("Invalid configuration for {}: {}", symbol, e);
+                        metrics::CONFIG_VALIDATION_FAILURES_TOTAL.inc();
                         continue;
                     }
-                    
+
                     // Store configuration
                     info!("Updating configuration for {}", symbol);
-                    info!("Configuration parameters: daily_return_bps={}, notional_per_side={}, interval={}", 
+                    info!("Configuration parameters: daily_return_bps={}, notional_per_side={}, interval={}",
                         config.daily_return_bps, config.notional_per_side, config.force_quote_refresh_interval);
-                    
+
                     let mut configs_write = self.configs.write().await;
                     configs_write.insert(symbol.clone(), config);
-                    
+
                     // Also store in Redis for persistence
                     if let Err(e) = self.store_config_in_redis(&symbol, &payload).await {
                         error!("Failed to store configuration in Redis: {}", e);
+                        metrics::REDIS_WRITE_ERRORS_TOTAL.inc();
+                    }
+
+                    metrics::CONFIG_UPDATES_TOTAL.inc();
+                },
+                Ok(ConfigMessage::Delete(symbol)) => {
+                    info!("Removing configuration for {}", symbol);
+
+                    self.configs.write().await.remove(&symbol);
+
+                    if let Err(e) = self.delete_config_from_redis(&symbol).await {
+                        error!("Failed to delete configuration from Redis: {}", e);
+                        metrics::REDIS_WRITE_ERRORS_TOTAL.inc();
                     }
+
+                    metrics::CONFIG_UPDATES_TOTAL.inc();
                 },
                 Err(e) => {
                     error!("Failed to parse configuration: {}", e);
                 }
             }
         }
-        
-        Ok(())
     }
-    
+
     /// Load all stored configurations from Redis
     pub async fn load_stored_configs(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Loading stored configurations from Redis");
-        
-        let mut conn = self.redis_client.get_async_connection().await?;
+
+        let mut conn = self.redis_pool.get().await?;
         
         // Get all keys with config: prefix
         let keys: Vec<String> = redis::cmd("KEYS")
@@ -122,51 +180,62 @@ impl ConfigService {
     
     /// Store configuration in Redis for persistence
     async fn store_config_in_redis(&self, symbol: &str, config_json: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut conn = self.redis_client.get_async_connection().await?;
+        let mut conn = self.redis_pool.get().await?;
         
         // Store configuration with symbol as key
         let key = format!("config:{}", symbol);
         conn.set::<_, _, ()>(key, config_json).await?;
-        
+
         Ok(())
     }
-    
+
+    /// Remove a deleted symbol's persisted configuration from Redis so it
+    /// isn't resurrected by `load_stored_configs` on the next restart.
+    async fn delete_config_from_redis(&self, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.redis_pool.get().await?;
+
+        let key = format!("config:{}", symbol);
+        conn.del::<_, ()>(key).await?;
+
+        Ok(())
+    }
+
     /// Validate configuration parameters
-    fn validate_config(&self, config: &MarketMakerConfig) -> Result<(), String> {
+    fn validate_config(&self, config: &MarketMakerConfig) -> Result<(), ConfigError> {
         // Check required fields
         if config.symbol.is_empty() {
-            return Err("Symbol cannot be empty".to_string());
+            return Err(ConfigError::EmptySymbol);
         }
-        
+
         // Check reasonable values
         if config.daily_return_bps == 0 {
-            return Err("Daily return BPS must be greater than 0".to_string());
+            return Err(ConfigError::InvalidDailyReturnBps);
         }
-        
+
         if config.notional_per_side <= 0.0 {
-            return Err("Notional per side must be greater than 0".to_string());
+            return Err(ConfigError::InvalidNotionalPerSide);
         }
-        
+
         if config.daily_pnl_stop_loss <= 0.0 {
-            return Err("Daily PNL stop loss must be greater than 0".to_string());
+            return Err(ConfigError::InvalidDailyPnlStopLoss);
         }
-        
+
         if config.trailing_take_profit <= 0.0 || config.trailing_take_profit >= 1.0 {
-            return Err("Trailing take profit must be between 0 and 1".to_string());
+            return Err(ConfigError::InvalidTrailingTakeProfit);
         }
-        
+
         if config.trailing_stop_loss <= 0.0 || config.trailing_stop_loss >= 1.0 {
-            return Err("Trailing stop loss must be between 0 and 1".to_string());
+            return Err(ConfigError::InvalidTrailingStopLoss);
         }
-        
+
         if config.force_quote_refresh_interval < 100 {
-            return Err("Force quote refresh interval must be at least 100ms".to_string());
+            return Err(ConfigError::RefreshIntervalTooShort);
         }
-        
+
         if config.max_long_usd < 0.0 || config.max_short_usd < 0.0 {
-            return Err("Position limits cannot be negative".to_string());
+            return Err(ConfigError::NegativePositionLimit);
         }
-        
+
         Ok(())
     }
-} 
\ No newline at end of file
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use actix_web::{get, post, web, App, HttpResponse, HttpServer};
+use log::info;
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::enhanced_market_maker::{MarketMakerConfig, Position};
+use crate::lifecycle_manager::{LifecycleManager, SymbolStatus};
+use crate::symbol_scanner::SymbolMetrics;
+
+/// Shared state backing the control server, one variant per `MODE`. Only the
+/// variant matching the running process's mode is ever constructed, since a
+/// process only runs one mode at a time.
+#[derive(Clone)]
+pub enum ControlServerState {
+    MarketMaker {
+        lifecycle: Arc<RwLock<LifecycleManager>>,
+        configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>,
+    },
+    SymbolScanner {
+        symbol_metrics: Arc<RwLock<HashMap<String, SymbolMetrics>>>,
+    },
+    ConfigService {
+        configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>,
+    },
+    PositionManager {
+        positions: Arc<RwLock<HashMap<String, Position>>>,
+    },
+}
+
+/// One entry of `ListMarketMakers`: `SymbolStatus` plus the symbol it's for,
+/// since the lifecycle manager keys its snapshot by symbol rather than
+/// carrying it inline.
+#[derive(Serialize)]
+struct MarketMakerStatus {
+    symbol: String,
+    #[serde(flatten)]
+    status: SymbolStatus,
+}
+
+/// Summary returned by `GET /status`, shaped differently per mode so each
+/// service reports what's actually meaningful for it.
+#[derive(Serialize)]
+#[serde(tag = "mode")]
+enum StatusSummary {
+    MarketMaker { configs_loaded: usize, symbols_tracked: usize },
+    SymbolScanner { symbols_tracked: usize },
+    ConfigService { configs_loaded: usize },
+    PositionManager { positions_tracked: usize },
+}
+
+#[get("/market-makers")]
+async fn list_market_makers(state: web::Data<ControlServerState>) -> HttpResponse {
+    match state.get_ref() {
+        ControlServerState::MarketMaker { lifecycle, .. } => {
+            let statuses = lifecycle.read().await.status_snapshot().await;
+            let body: Vec<MarketMakerStatus> = statuses
+                .into_iter()
+                .map(|(symbol, status)| MarketMakerStatus { symbol, status })
+                .collect();
+            HttpResponse::Ok().json(body)
+        }
+        _ => HttpResponse::NotFound().body("not running in MarketMaker mode"),
+    }
+}
+
+#[get("/market-makers/{symbol}")]
+async fn get_market_maker(path: web::Path<String>, state: web::Data<ControlServerState>) -> HttpResponse {
+    let symbol = path.into_inner();
+
+    match state.get_ref() {
+        ControlServerState::MarketMaker { lifecycle, .. } => {
+            match lifecycle.read().await.status_snapshot().await.remove(&symbol) {
+                Some(status) => HttpResponse::Ok().json(MarketMakerStatus { symbol, status }),
+                None => HttpResponse::NotFound().body(format!("no instance tracked for {}", symbol)),
+            }
+        }
+        _ => HttpResponse::NotFound().body("not running in MarketMaker mode"),
+    }
+}
+
+async fn set_trading(state: web::Data<ControlServerState>, symbol: String, enabled: bool) -> HttpResponse {
+    match state.get_ref() {
+        ControlServerState::MarketMaker { lifecycle, .. } => {
+            if lifecycle.read().await.set_enable_trading(&symbol, enabled).await {
+                HttpResponse::Ok().finish()
+            } else {
+                HttpResponse::NotFound().body(format!("no live instance for {}", symbol))
+            }
+        }
+        _ => HttpResponse::NotFound().body("not running in MarketMaker mode"),
+    }
+}
+
+#[post("/market-makers/{symbol}/pause")]
+async fn pause_symbol(path: web::Path<String>, state: web::Data<ControlServerState>) -> HttpResponse {
+    set_trading(state, path.into_inner(), false).await
+}
+
+#[post("/market-makers/{symbol}/resume")]
+async fn resume_symbol(path: web::Path<String>, state: web::Data<ControlServerState>) -> HttpResponse {
+    set_trading(state, path.into_inner(), true).await
+}
+
+#[get("/status")]
+async fn status(state: web::Data<ControlServerState>) -> HttpResponse {
+    let summary = match state.get_ref() {
+        ControlServerState::MarketMaker { lifecycle, configs } => StatusSummary::MarketMaker {
+            configs_loaded: configs.read().await.len(),
+            symbols_tracked: lifecycle.read().await.state_snapshot().len(),
+        },
+        ControlServerState::SymbolScanner { symbol_metrics } => {
+            StatusSummary::SymbolScanner { symbols_tracked: symbol_metrics.read().await.len() }
+        }
+        ControlServerState::ConfigService { configs } => {
+            StatusSummary::ConfigService { configs_loaded: configs.read().await.len() }
+        }
+        ControlServerState::PositionManager { positions } => {
+            StatusSummary::PositionManager { positions_tracked: positions.read().await.len() }
+        }
+    };
+
+    HttpResponse::Ok().json(summary)
+}
+
+/// Serve introspection/control endpoints over HTTP at `bind_addr` (e.g.
+/// `0.0.0.0:9101`): `GET /status` on every mode, plus `GET /market-makers`,
+/// `GET /market-makers/{symbol}`, and `POST /market-makers/{symbol}/pause`
+/// `resume` when running as `MarketMaker`.
+pub async fn run_control_server(bind_addr: &str, state: ControlServerState) -> std::io::Result<()> {
+    info!("Starting control server on {}", bind_addr);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(list_market_makers)
+            .service(get_market_maker)
+            .service(pause_symbol)
+            .service(resume_symbol)
+            .service(status)
+    })
+    .bind(bind_addr)?
+    .run()
+    .await
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
@@ -10,6 +10,7 @@ use futures::StreamExt;
 use tokio::sync::mpsc::UnboundedSender;
 use std::sync::atomic::{AtomicBool, Ordering};
 use redis::{Client as RedisClient, AsyncCommands};
+use tokio_util::sync::CancellationToken;
 
 use hyperliquid_rust_sdk::{
     MarketMaker as SdkMarketMaker,
@@ -18,20 +19,26 @@ use hyperliquid_rust_sdk::{
     BaseUrl,
     ExchangeClient,
     InfoClient,
-    bps_diff,
     truncate_float,
     EPSILON,
     ClientCancelRequest,
-    ClientOrderRequest,
-    ClientOrder,
-    ClientLimit,
     MarketOrderParams,
-    ExchangeResponseStatus,
-    ExchangeDataStatus,
     UserStateResponse,
     Message,
     Subscription,
     Meta,
+    UserFills,
+};
+
+use crate::price_source::RatePriceSource;
+use crate::reconciliation::{diff_orders, execute_plan, DesiredOrder};
+use crate::backtest::MAX_NUM_LIMIT_ORDERS;
+use crate::candle_aggregator::{CandleAggregator, CandleSource, persist_completed_candle, persist_latest_bar};
+use crate::candle_backfill::Resolution;
+use crate::redis_pool::RedisPool;
+use crate::position_events::{
+    position_update_channel, PositionDelta, PositionSnapshot, PositionUpdateEvent,
+    PositionUpdateReceiver, PositionUpdateSender,
 };
 
 /// Structure for a single quote level in the market maker
@@ -42,6 +49,18 @@ pub struct QuoteLevel {
     pub size_multiplier: f64,      // Multiplier on the base size (1.0 = base size)
 }
 
+/// A recurring UTC boundary at which the bot forcibly flattens and halts
+/// quoting, independent of `check_risk_limits`'s stop-loss/take-profit
+/// checks - e.g. to respect an exchange's or desk's trading session window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionExpirySchedule {
+    /// Flatten every day at `utc_hour` (0-23)
+    Daily { utc_hour: u32 },
+    /// Flatten once a week, on `utc_weekday` (0 = Sunday) at `utc_hour`
+    Weekly { utc_weekday: u32, utc_hour: u32 },
+}
+
 /// Configuration for the enhanced market maker
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MarketMakerConfig {
@@ -60,6 +79,53 @@ pub struct MarketMakerConfig {
     #[serde(default = "default_quote_levels")]
     pub quote_levels: Vec<QuoteLevel>,
     pub vault_address: Option<String>,
+    /// Percentage markup applied above the reference mid when quoting asks,
+    /// on top of the `daily_return_bps` half-spread (e.g. 0.001 = 0.1%)
+    #[serde(default)]
+    pub ask_spread: f64,
+    /// Percentage markup applied below the reference mid when quoting bids,
+    /// on top of the `daily_return_bps` half-spread
+    #[serde(default)]
+    pub bid_spread: f64,
+    /// Maximum age (ms) the last positive mid price may reach before it's
+    /// considered stale; quoting is skipped and resting orders are cancelled
+    /// past this. The age is checked only when an `AllMids` update for the
+    /// symbol arrives, and a positive mid resets it first, so this fires when
+    /// a non-positive mid arrives more than this long after the last positive
+    /// one; a feed that stops sending updates is not detected by this check.
+    #[serde(default = "default_max_mid_staleness_ms")]
+    pub max_mid_staleness_ms: u64,
+    /// UTC hour (0-23) at which the daily PnL counters roll over; defaults
+    /// to midnight UTC
+    #[serde(default = "default_rollover_utc_hour")]
+    pub rollover_utc_hour: u32,
+    /// Optional recurring session-expiry schedule; when its boundary is
+    /// crossed the bot force-flattens and halts quoting regardless of
+    /// `check_risk_limits`. `None` disables scheduled session expiry.
+    #[serde(default)]
+    pub session_expiry: Option<SessionExpirySchedule>,
+    /// When a session-expiry flatten fires, re-enable quoting immediately
+    /// for the next session instead of leaving `enable_trading` false until
+    /// an operator manually re-enables it
+    #[serde(default)]
+    pub auto_rollover: bool,
+    /// Monotonically increasing version, bumped on every successful write
+    /// by `store_config_in_redis`. `update_config` stamps it with the
+    /// version this instance last saw before writing; the write only commits if
+    /// Redis still holds that version, so two operators racing to update
+    /// the same symbol can't silently clobber one another.
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Default staleness threshold for the mid price feed: 30 seconds
+fn default_max_mid_staleness_ms() -> u64 {
+    30_000
+}
+
+/// Default daily PnL rollover boundary is midnight UTC
+fn default_rollover_utc_hour() -> u32 {
+    0
 }
 
 /// Default value for enable_trading is true
@@ -76,33 +142,19 @@ fn default_quote_levels() -> Vec<QuoteLevel> {
     }]
 }
 
-/// Helper function to check if two quote level vectors are different
-fn quote_levels_changed(a: &[QuoteLevel], b: &[QuoteLevel]) -> bool {
-    // First check if lengths are different
-    if a.len() != b.len() {
-        return true;
-    }
+/// Content hash of `config`, excluding `version` itself, so a writer that
+/// forgot to bump `version` but still changed the content is still
+/// detected by the version/hash short-circuit in `apply_config_from_redis`.
+pub(crate) fn config_content_hash(config: &MarketMakerConfig) -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
-    // Then check each level
-    for (i, level_a) in a.iter().enumerate() {
-        let level_b = &b[i];
-        if level_a.level != level_b.level 
-            || (level_a.spread_multiplier - level_b.spread_multiplier).abs() > EPSILON
-            || (level_a.size_multiplier - level_b.size_multiplier).abs() > EPSILON {
-            return true;
-        }
-    }
-    
-    false
-}
+    let mut unversioned = config.clone();
+    unversioned.version = 0;
 
-/// Helper function to compare two Option<String> values
-fn option_string_changed(a: &Option<String>, b: &Option<String>) -> bool {
-    match (a, b) {
-        (Some(a_val), Some(b_val)) => a_val != b_val,
-        (None, None) => false,
-        _ => true, // One is Some and one is None
-    }
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&unversioned).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
 }
 
 /// Add this new struct for shared configuration data
@@ -115,6 +167,56 @@ struct SharedMarketMakerParams {
     price_decimals: u32,
     last_quote_time: u64,
     needs_refresh: bool,
+    /// Asset metadata fetched at startup, shared so `slow_path_check_config`
+    /// can derive price precision with the same `price_decimals_for_symbol`
+    /// helper the main loop uses instead of a second hardcoded table.
+    asset_meta: Option<Meta>,
+}
+
+/// Bound on how long a `SharedMarketMakerParams` lock acquisition waits
+/// before giving up, so a wedged holder logs and the caller bails instead of
+/// deadlocking the slow path / keyspace subscriber forever.
+const PARAMS_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Bound on how many recent fill `tid`s `apply_user_fills` remembers for
+/// dedup, so a WS reconnect replaying fills we've already credited can't
+/// double-count `realized_daily_pnl`. Oldest ids are evicted once this is
+/// exceeded rather than growing unbounded across a long session.
+const MAX_TRACKED_FILL_IDS: usize = 4096;
+
+/// Take the `SharedMarketMakerParams` lock exactly once and return an owned
+/// snapshot, instead of re-locking (and potentially re-reading a different
+/// version of `config` each time) for every decision that needs it. Returns
+/// `None` (after logging) if the lock isn't acquired within
+/// `PARAMS_LOCK_TIMEOUT`.
+async fn read_params_snapshot(
+    params: &Arc<Mutex<SharedMarketMakerParams>>,
+    log_prefix: &str,
+) -> Option<SharedMarketMakerParams> {
+    match tokio::time::timeout(PARAMS_LOCK_TIMEOUT, params.lock()).await {
+        Ok(guard) => Some(guard.clone()),
+        Err(_) => {
+            error!("{} Timed out waiting {:?} for SharedMarketMakerParams lock", log_prefix, PARAMS_LOCK_TIMEOUT);
+            None
+        }
+    }
+}
+
+/// Take the `SharedMarketMakerParams` lock exactly once, apply `mutate` to
+/// it, and return its result - the single-write counterpart to
+/// `read_params_snapshot`. Returns `None` (after logging) on lock timeout.
+async fn write_params<T>(
+    params: &Arc<Mutex<SharedMarketMakerParams>>,
+    log_prefix: &str,
+    mutate: impl FnOnce(&mut SharedMarketMakerParams) -> T,
+) -> Option<T> {
+    match tokio::time::timeout(PARAMS_LOCK_TIMEOUT, params.lock()).await {
+        Ok(mut guard) => Some(mutate(&mut guard)),
+        Err(_) => {
+            error!("{} Timed out waiting {:?} for SharedMarketMakerParams lock", log_prefix, PARAMS_LOCK_TIMEOUT);
+            None
+        }
+    }
 }
 
 /// Enhanced version of the SDK's MarketMakerRestingOrder that includes the order side
@@ -124,6 +226,40 @@ pub struct EnhancedRestingOrder {
     pub position: f64,
     pub price: f64,
     pub is_bid: bool,  // True for buy orders, false for sell orders
+    /// Ladder rung this order quotes (1 is closest to mid, as in
+    /// `QuoteLevel::level`). Irrelevant for
+    /// trigger markers, which carry 0 and are excluded from rung matching.
+    pub level: u16,
+    /// `Some` if this entry is a stop-loss/take-profit trigger marker rather
+    /// than a real resting quote order
+    pub trigger_kind: Option<TriggerKind>,
+    /// Size requested when the order was placed
+    pub original_size: f64,
+    /// Cumulative size filled against this `oid`, summed across however many
+    /// partial fills the exchange reports against it
+    pub filled_size: f64,
+}
+
+impl EnhancedRestingOrder {
+    /// Unfilled size still resting on the book
+    pub fn remaining(&self) -> f64 {
+        (self.original_size - self.filled_size).max(0.0)
+    }
+}
+
+/// Which kind of risk-exit condition a trigger marker in `active_orders` represents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerKind {
+    StopLoss,
+    TakeProfit,
+}
+
+/// Trigger marker oids are allocated from the top half of the u64 space so
+/// they can never collide with real exchange-assigned order ids
+static NEXT_TRIGGER_OID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(u64::MAX / 2);
+
+fn next_trigger_oid() -> u64 {
+    NEXT_TRIGGER_OID.fetch_add(1, Ordering::Relaxed)
 }
 
 /// Enhanced market maker that builds upon the SDK's basic market maker
@@ -138,8 +274,50 @@ pub struct EnhancedMarketMaker {
     pub lowest_pnl: f64,
     last_quote_time: u64,
     day_start_timestamp: u64, // Track when the trading day started
+    /// Timestamp (ms) of the most recent session-expiry boundary this bot
+    /// has already flattened for; distinct from `day_start_timestamp`
+    /// because a session schedule can be weekly rather than daily
+    session_start_timestamp: u64,
     asset_meta: Option<Meta>, // Store asset metadata retrieved from the API
-    redis_client: RedisClient, // Redis client for config updates
+    /// Pooled, auto-reconnecting connection for all regular GET/SET/script
+    /// Redis traffic (candles, PnL state, config CAS writes)
+    redis_pool: RedisPool,
+    /// Raw client kept only for the long-lived keyspace-notification
+    /// subscription, which needs a dedicated connection rather than one
+    /// checked out of `redis_pool`
+    redis_client: RedisClient,
+    /// Timestamp (ms) of the last accepted (strictly-positive) mid price update
+    last_mid_update_ms: u64,
+    /// Whether we've ever read a strictly-positive mid price; stays false
+    /// until the first usable tick so we never quote off an uninitialized
+    /// zero/garbage price
+    has_stable_price: bool,
+    /// Optional independent reference price feed; when set, quotes are
+    /// derived from it instead of Hyperliquid's own `current_mid_price`
+    reference_price_source: Option<Arc<dyn RatePriceSource>>,
+    /// Live OHLCV bars built from Hyperliquid's own mid-price ticks
+    mid_candles: CandleAggregator,
+    /// Live OHLCV bars built from this maker's own executed fills
+    fill_candles: CandleAggregator,
+    /// Broadcasts a `PositionUpdateEvent` whenever a fill or user-state poll
+    /// moves size, entry price, unrealized PnL, or realized PnL
+    position_events: PositionUpdateSender,
+    /// Mirrors `config.enable_trading` but lives behind an `Arc` so
+    /// `LifecycleManager` can keep a clone and let a control-server
+    /// pause/resume flip it from outside, even though this instance itself
+    /// is moved into its quoting task for as long as that task runs.
+    trading_enabled: Arc<AtomicBool>,
+    /// Fill `tid`s `apply_user_fills` has already credited to
+    /// `realized_daily_pnl`, so a WS reconnect's initial snapshot or a
+    /// redelivered fill can't double-count. `fill_ids_order` tracks insertion
+    /// order so the set can be trimmed back to `MAX_TRACKED_FILL_IDS`.
+    seen_fill_ids: HashSet<u64>,
+    fill_ids_order: VecDeque<u64>,
+}
+
+/// Candle resolutions the live aggregators build bars at
+fn default_candle_resolutions() -> Vec<Resolution> {
+    vec![Resolution::OneMinute, Resolution::FiveMinutes, Resolution::OneHour]
 }
 
 /// Position information
@@ -151,11 +329,36 @@ pub struct Position {
     pub current_price: f64,
     pub unrealized_pnl: f64,
     pub notional_usd: f64,
+    /// Monotonically increasing per-symbol version, bumped on every accepted
+    /// write; out-of-order writes are rejected by `last_update_ts`
+    pub version: u64,
+    /// Timestamp (ms) this position's data was sourced from
+    pub last_update_ts: u64,
+    /// Cumulative PnL realized by fills that have closed or reduced this
+    /// position, as distinct from `unrealized_pnl` on the remaining size
+    pub realized_pnl: f64,
 }
 
 impl EnhancedMarketMaker {
     /// Create a new enhanced market maker
-    pub async fn new(config: MarketMakerConfig, wallet: LocalWallet, redis_client: RedisClient) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn new(
+        config: MarketMakerConfig,
+        wallet: LocalWallet,
+        redis_pool: RedisPool,
+        redis_client: RedisClient,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new_with_reference_price_source(config, wallet, redis_pool, redis_client, None).await
+    }
+
+    /// Create a new enhanced market maker that quotes off `reference_price_source`
+    /// (e.g. an external venue's ticker) instead of Hyperliquid's own mid price
+    pub async fn new_with_reference_price_source(
+        config: MarketMakerConfig,
+        wallet: LocalWallet,
+        redis_pool: RedisPool,
+        redis_client: RedisClient,
+        reference_price_source: Option<Arc<dyn RatePriceSource>>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         // Fetch asset metadata first to get precision information
         let base_url = if cfg!(feature = "testnet") { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
         let info_client = InfoClient::new(None, base_url.clone()).await?;
@@ -170,18 +373,10 @@ impl EnhancedMarketMaker {
             }
         };
         
-        // Get the appropriate price precision for this symbol from hardcoded values
-        // We can't use self.get_price_decimals_for_symbol yet as we're still constructing self
-        let price_decimals = match config.symbol.as_str() {
-            "BTC" => 0,
-            "ETH" => 2,
-            "SOL" => 3,
-            "AVAX" => 3,
-            "MATIC" => 4,
-            "DOGE" => 6,
-            "SHIB" => 8,
-            _ => 2,
-        };
+        // Get the appropriate price precision for this symbol from the freshly
+        // fetched asset metadata. We can't call self.get_price_decimals_for_symbol
+        // yet as we're still constructing self, so call the free function directly.
+        let price_decimals = price_decimals_for_symbol(asset_meta.as_ref(), &config.symbol);
         
         // Create the basic market maker input from our enhanced config with correct precision
         let market_maker_input = MarketMakerInput {
@@ -197,27 +392,123 @@ impl EnhancedMarketMaker {
         // Create the base market maker from SDK
         let market_maker = SdkMarketMaker::new(market_maker_input).await;
         
-        info!("Created market maker for {} with price precision of {} decimals", 
+        info!("Created market maker for {} with price precision of {} decimals",
             config.symbol, price_decimals);
-        
+
+        // Build the live candle aggregators and restore their in-progress
+        // bars from Redis so a short disconnect/restart doesn't leave a
+        // visible hole in the series
+        let mut mid_candles = CandleAggregator::new(config.symbol.clone(), CandleSource::Mid, default_candle_resolutions());
+        if let Err(e) = mid_candles.backfill_from_redis(&redis_pool).await {
+            warn!("Failed to backfill mid candles from Redis for {}: {}", config.symbol, e);
+        }
+        let mut fill_candles = CandleAggregator::new(config.symbol.clone(), CandleSource::Fill, default_candle_resolutions());
+        if let Err(e) = fill_candles.backfill_from_redis(&redis_pool).await {
+            warn!("Failed to backfill fill candles from Redis for {}: {}", config.symbol, e);
+        }
+
+        // Resume the daily PnL counters from Redis, if any were persisted,
+        // so a restart mid-session doesn't silently zero the trailing
+        // stop-loss/take-profit baselines. If the persisted
+        // `day_start_timestamp` turns out to be stale (the process was down
+        // across a rollover boundary), `check_and_reset_daily_pnl`'s next
+        // poll rolls it over correctly on its own.
+        let (daily_pnl, realized_daily_pnl, highest_pnl, lowest_pnl, day_start_timestamp) =
+            match load_pnl_state_from_redis(&redis_pool, &config.symbol).await {
+                Ok(Some(state)) => {
+                    info!("Resumed daily PnL state from Redis for {}", config.symbol);
+                    state
+                }
+                Ok(None) => (0.0, 0.0, 0.0, 0.0, current_timestamp_ms()),
+                Err(e) => {
+                    warn!("Failed to load daily PnL state from Redis for {}: {}", config.symbol, e);
+                    (0.0, 0.0, 0.0, 0.0, current_timestamp_ms())
+                }
+            };
+
+        let trading_enabled = Arc::new(AtomicBool::new(config.enable_trading));
+
         Ok(EnhancedMarketMaker {
             config,
             market_maker,
             positions: HashMap::new(),
             current_mid_price: 0.0,
-            daily_pnl: 0.0,
-            realized_daily_pnl: 0.0,
-            highest_pnl: 0.0,
-            lowest_pnl: 0.0,
+            daily_pnl,
+            realized_daily_pnl,
+            highest_pnl,
+            lowest_pnl,
             last_quote_time: 0,
-            day_start_timestamp: current_timestamp_ms(),
+            day_start_timestamp,
+            session_start_timestamp: current_timestamp_ms(),
             asset_meta,
+            redis_pool,
             redis_client,
+            reference_price_source,
+            last_mid_update_ms: 0,
+            has_stable_price: false,
+            mid_candles,
+            fill_candles,
+            position_events: position_update_channel(),
+            trading_enabled,
+            seen_fill_ids: HashSet::new(),
+            fill_ids_order: VecDeque::new(),
         })
     }
 
+    /// Shared flag mirroring `config.enable_trading`, for a control server
+    /// (via `LifecycleManager`) to pause/resume this instance from outside
+    /// even once it's been moved into its own quoting task.
+    pub fn trading_enabled_handle(&self) -> Arc<AtomicBool> {
+        self.trading_enabled.clone()
+    }
+
+    /// Subscribe to this maker's position/PnL update stream, e.g. from a
+    /// dashboard or a supervising process that wants to watch several
+    /// symbols live instead of polling `self.positions`. Each subscriber
+    /// gets its own receiver and only sees events published after it
+    /// subscribed.
+    pub fn subscribe_position_updates(&self) -> PositionUpdateReceiver {
+        self.position_events.subscribe()
+    }
+
+    /// Feed one mid-price tick into the live candle aggregator and persist
+    /// any bars that just completed, plus the still-forming bar, to Redis
+    async fn ingest_mid_candle_tick(&mut self, timestamp_ms: u64, mid_price: f64) {
+        let completed = self.mid_candles.ingest(timestamp_ms, mid_price, 0.0);
+        for resolution in [Resolution::OneMinute, Resolution::FiveMinutes, Resolution::OneHour] {
+            if let Some(candle) = completed.iter().find(|c| c.interval == resolution.as_str()) {
+                if let Err(e) = persist_completed_candle(&self.redis_pool, CandleSource::Mid, resolution, candle).await {
+                    warn!("Failed to persist completed mid candle for {}: {}", self.config.symbol, e);
+                }
+            }
+            if let Some(candle) = self.mid_candles.current_bars().find(|c| c.interval == resolution.as_str()) {
+                if let Err(e) = persist_latest_bar(&self.redis_pool, CandleSource::Mid, resolution, candle).await {
+                    warn!("Failed to persist latest mid candle for {}: {}", self.config.symbol, e);
+                }
+            }
+        }
+    }
+
+    /// Feed one fill print into the live candle aggregator and persist any
+    /// bars that just completed, plus the still-forming bar, to Redis
+    async fn ingest_fill_candle_tick(&mut self, timestamp_ms: u64, fill_price: f64, fill_size: f64) {
+        let completed = self.fill_candles.ingest(timestamp_ms, fill_price, fill_size);
+        for resolution in [Resolution::OneMinute, Resolution::FiveMinutes, Resolution::OneHour] {
+            if let Some(candle) = completed.iter().find(|c| c.interval == resolution.as_str()) {
+                if let Err(e) = persist_completed_candle(&self.redis_pool, CandleSource::Fill, resolution, candle).await {
+                    warn!("Failed to persist completed fill candle for {}: {}", self.config.symbol, e);
+                }
+            }
+            if let Some(candle) = self.fill_candles.current_bars().find(|c| c.interval == resolution.as_str()) {
+                if let Err(e) = persist_latest_bar(&self.redis_pool, CandleSource::Fill, resolution, candle).await {
+                    warn!("Failed to persist latest fill candle for {}: {}", self.config.symbol, e);
+                }
+            }
+        }
+    }
+
     /// Start the market maker
-    pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn start(&mut self, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Starting enhanced market maker for {}", self.config.symbol);
         
         // Initialize position tracking
@@ -230,16 +521,19 @@ impl EnhancedMarketMaker {
                 current_price: 0.0,
                 unrealized_pnl: 0.0,
                 notional_usd: 0.0,
+                version: 0,
+                last_update_ts: current_timestamp_ms(),
+                realized_pnl: 0.0,
             },
         );
         
         // Instead of using the base market maker's start method,
         // we implement our own logic to handle our enhanced features
-        self.run_market_making_loop().await
+        self.run_market_making_loop(shutdown).await
     }
-    
+
     /// The main market making loop with enhanced features
-    async fn run_market_making_loop(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn run_market_making_loop(&mut self, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Subscribe to necessary data feeds and handle events with our enhanced logic
         info!("Market making loop started for {}", self.config.symbol);
         
@@ -284,9 +578,15 @@ impl EnhancedMarketMaker {
         
         // Subscribe to AllMids to get price updates
         info_client.subscribe(Subscription::AllMids, sender.clone()).await?;
-        
+
         // Get initial user state
         let user_address = wallet.address();
+
+        // Subscribe to our own fills so partial fills against a resting
+        // order's oid can be aggregated as they arrive, instead of inferring
+        // remaining size only from the next full user-state snapshot
+        info_client.subscribe(Subscription::UserFills { user: user_address }, sender.clone()).await?;
+
         let user_state = info_client.user_state(user_address).await?;
         let symbol_clone = self.config.symbol.clone();
         self.update_position_from_user_state(&user_state, &symbol_clone).await?;
@@ -303,11 +603,12 @@ impl EnhancedMarketMaker {
             price_decimals: self.market_maker.decimals,
             last_quote_time: self.last_quote_time,
             needs_refresh: false,
+            asset_meta: self.asset_meta.clone(),
         }));
         
         // Setup slow_path timer for config updates
         let symbol_for_redis = self.config.symbol.clone();
-        let redis_client_clone = self.redis_client.clone();
+        let redis_pool_clone = self.redis_pool.clone();
         
         // The exchange client needs to be shared between the main loop and slow_path
         let exchange_client_arc = Arc::new(Mutex::new(exchange_client));
@@ -328,8 +629,8 @@ impl EnhancedMarketMaker {
                 
                 // Check Redis for updated config
                 if let Err(e) = slow_path_check_config(
-                    &redis_client_clone, 
-                    &symbol_for_redis, 
+                    &redis_pool_clone,
+                    &symbol_for_redis,
                     params_clone.clone(),
                     &exchange_client_clone,
                     &active_orders_clone
@@ -338,25 +639,54 @@ impl EnhancedMarketMaker {
                 }
             }
         });
-        
+
+        // Also react to config updates immediately via Redis keyspace
+        // notifications, rather than waiting for the next slow-path tick.
+        // The slow-path poll above stays running as a fallback in case a
+        // notification is dropped (e.g. reconnect window).
+        let redis_pool_for_keyspace = self.redis_pool.clone();
+        let redis_client_for_keyspace = self.redis_client.clone();
+        let symbol_for_keyspace = self.config.symbol.clone();
+        let params_for_keyspace = shared_params.clone();
+        let exchange_client_for_keyspace = exchange_client_arc.clone();
+        let active_orders_for_keyspace = active_orders_arc.clone();
+        tokio::spawn(async move {
+            subscribe_config_keyspace_events(
+                redis_pool_for_keyspace,
+                redis_client_for_keyspace,
+                symbol_for_keyspace,
+                params_for_keyspace,
+                exchange_client_for_keyspace,
+                active_orders_for_keyspace,
+            ).await;
+        });
+
         // Use the mutex-protected data in the main loop - avoid holding locks across await points
-        while let Some(message) = receiver.recv().await {
+        loop {
+            let message = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Shutdown requested for {}, cancelling resting quote orders", self.config.symbol);
+                    self.cancel_resting_quote_orders(&exchange_client_arc, &active_orders_arc).await;
+                    self.persist_pnl_state();
+                    return Ok(());
+                }
+                message = receiver.recv() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+            };
+
             // Update our local params from the shared object - Use a separate block to ensure lock is released
             let force_refresh = {
                 let shared = shared_params.lock().await;
                 
-                let needs_update = self.config.daily_return_bps != shared.config.daily_return_bps ||
-                                   self.config.notional_per_side != shared.config.notional_per_side ||
-                                   self.config.force_quote_refresh_interval != shared.config.force_quote_refresh_interval ||
-                                   self.config.enable_trading != shared.config.enable_trading ||
-                                   self.config.daily_pnl_stop_loss != shared.config.daily_pnl_stop_loss ||
-                                   self.config.trailing_take_profit != shared.config.trailing_take_profit ||
-                                   self.config.trailing_stop_loss != shared.config.trailing_stop_loss ||
-                                   self.config.hedge_only_mode != shared.config.hedge_only_mode ||
-                                   self.config.max_long_usd != shared.config.max_long_usd ||
-                                   self.config.max_short_usd != shared.config.max_short_usd ||
-                                   quote_levels_changed(&self.config.quote_levels, &shared.config.quote_levels) ||
-                                   option_string_changed(&self.config.vault_address, &shared.config.vault_address);
+                // Compare by content hash rather than an explicit per-field
+                // diff: a field added to `MarketMakerConfig` without also
+                // being added here used to be silently ignored (e.g.
+                // bid_spread/ask_spread, max_mid_staleness_ms,
+                // rollover_utc_hour/session_expiry/auto_rollover all missed
+                // a change until some other, listed field also changed).
+                let needs_update = config_content_hash(&self.config) != config_content_hash(&shared.config);
                 
                 if needs_update {
                     info!("Main loop detected config change in one or more parameters. Updating configuration.");
@@ -371,6 +701,7 @@ impl EnhancedMarketMaker {
                     
                     // Update internal configuration
                     self.config = shared.config.clone();
+                    self.trading_enabled.store(self.config.enable_trading, Ordering::Relaxed);
                     self.market_maker.half_spread = shared.half_spread;
                     self.market_maker.target_liquidity = shared.target_liquidity;
                     self.market_maker.max_absolute_position_size = shared.max_position_size;
@@ -397,8 +728,17 @@ impl EnhancedMarketMaker {
                     let all_mids = all_mids.data.mids;
                     if let Some(mid_price_str) = all_mids.get(&self.config.symbol) {
                         if let Ok(mid_price) = mid_price_str.parse::<f64>() {
-                            self.current_mid_price = mid_price;
-                            
+                            // Only accept a strictly-positive mid as a "stable price" -
+                            // never quote off an uninitialized zero/garbage oracle tick
+                            if mid_price > 0.0 {
+                                self.current_mid_price = mid_price;
+                                self.last_mid_update_ms = now;
+                                self.has_stable_price = true;
+                                self.ingest_mid_candle_tick(now, mid_price).await;
+                            } else {
+                                warn!("Received non-positive mid price for {}: {}. Ignoring tick.", self.config.symbol, mid_price);
+                            }
+
                             // Update position data in a separate block to minimize lock time
                             {
                                 if let Ok(user_state) = info_client.user_state(user_address).await {
@@ -406,10 +746,62 @@ impl EnhancedMarketMaker {
                                     self.update_position_from_user_state(&user_state, &symbol_clone).await?;
                                 }
                             }
-                            
+
+                            // Skip quoting entirely while we have no stable price yet, or the
+                            // feed has gone stale, and cancel any resting orders so we don't
+                            // keep quoting off a price that can no longer be trusted
+                            let mid_age_ms = now.saturating_sub(self.last_mid_update_ms);
+                            if !self.has_stable_price || mid_age_ms > self.config.max_mid_staleness_ms {
+                                warn!("Mid price for {} is {} (age {} ms, max {} ms); skipping quoting and cancelling resting orders",
+                                    self.config.symbol,
+                                    if self.has_stable_price { "stale" } else { "not yet initialized" },
+                                    mid_age_ms, self.config.max_mid_staleness_ms);
+
+                                // Trigger markers aren't real exchange orders; only cancel genuine quotes
+                                let active_orders = active_orders_arc.lock().await.clone();
+                                let quote_oids: Vec<u64> = active_orders.iter()
+                                    .filter(|(_, o)| o.trigger_kind.is_none())
+                                    .map(|(oid, _)| *oid)
+                                    .collect();
+                                if !quote_oids.is_empty() {
+                                    let cancel_requests = quote_oids.iter()
+                                        .map(|oid| ClientCancelRequest {
+                                            asset: self.config.symbol.clone(),
+                                            oid: *oid,
+                                        })
+                                        .collect::<Vec<_>>();
+
+                                    let exchange_client_lock = exchange_client_arc.lock().await;
+                                    if let Err(e) = exchange_client_lock.bulk_cancel(cancel_requests, None).await {
+                                        warn!("Failed to cancel orders during staleness guard: {}", e);
+                                    } else {
+                                        let mut active_orders_lock = active_orders_arc.lock().await;
+                                        active_orders_lock.retain(|_, o| o.trigger_kind.is_some());
+                                        info!("Stale price guard cancelled all {} resting quote orders", quote_oids.len());
+                                    }
+                                }
+
+                                // Sleep a bit to not overwhelm the CPU, then move on to the next message
+                                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                                continue;
+                            }
+
+                            // Maintain resting stop-loss/take-profit trigger markers on every
+                            // tick, independent of `enable_trading` and the quote refresh
+                            // interval, so a risk exit still fires while trading is toggled off
+                            if let Err(e) = self.maintain_risk_trigger_orders(&active_orders_arc).await {
+                                error!("Failed to maintain risk trigger orders for {}: {}", self.config.symbol, e);
+                            }
+
+                            // Force a flatten at the configured session-expiry boundary, checked
+                            // every tick and independent of check_risk_limits so it fires promptly
+                            if let Err(e) = self.check_session_expiry(&active_orders_arc, &exchange_client_arc).await {
+                                error!("Failed to check session expiry for {}: {}", self.config.symbol, e);
+                            }
+
                             // Check if we need to refresh quotes based on the config interval
                             let should_refresh = now - self.last_quote_time > self.config.force_quote_refresh_interval;
-                            
+
                             if should_refresh {
                                 // Update timestamp before any awaits to avoid duplicate refreshes
                                 self.last_quote_time = now;
@@ -425,35 +817,45 @@ impl EnhancedMarketMaker {
                                     let price_decimals = self.get_price_decimals_for_symbol(&self.config.symbol);
                                     info!("Using {} decimal precision for {} prices", price_decimals, self.config.symbol);
                                     
+                                    // Use an independent reference price feed when configured, so we
+                                    // quote Hyperliquid off a more liquid external book; fall back to
+                                    // Hyperliquid's own mid if the reference feed has nothing yet
+                                    let reference_mid = match &self.reference_price_source {
+                                        Some(source) => source.reference_mid(&self.config.symbol).await.unwrap_or(self.current_mid_price),
+                                        None => self.current_mid_price,
+                                    };
+
                                     // Get current position and limits
                                     let position_size = self.get_position_size(&self.config.symbol);
-                                    let max_long = self.config.max_long_usd / self.current_mid_price;
-                                    let max_short = self.config.max_short_usd / self.current_mid_price;
-                                    
+                                    let max_long = self.config.max_long_usd / reference_mid;
+                                    let max_short = self.config.max_short_usd / reference_mid;
+
                                     // Get size precision for this symbol
                                     let size_decimals = self.get_size_decimals_for_symbol(&self.config.symbol);
                                     info!("Using {} decimal precision for {} order sizes", size_decimals, self.config.symbol);
-                                    
+
                                     // Calculate desired orders for each level
-                                    let mut desired_orders: Vec<(bool, f64, f64)> = Vec::new(); // (is_bid, price, size)
-                                    
+                                    let mut desired_orders: Vec<(bool, u16, f64, f64)> = Vec::new(); // (is_bid, level, price, size)
+
                                     // Base half spread calculation (in price units, not BPS)
-                                    let base_half_spread = self.current_mid_price * self.config.daily_return_bps as f64 / 10000.0;
-                                    
+                                    let base_half_spread = reference_mid * self.config.daily_return_bps as f64 / 10000.0;
+
                                     // Calculate orders for each level
                                     for level in &self.config.quote_levels {
                                         // Calculate spread for this level
                                         let level_spread = base_half_spread * level.spread_multiplier;
-                                        
-                                        // Calculate bid and ask prices for this level
+
+                                        // Calculate bid and ask prices for this level, applying the
+                                        // configured markup against the reference price on top of the
+                                        // daily-return-derived spread
                                         let bid_price = truncate_float(
-                                            self.current_mid_price - level_spread,
+                                            reference_mid * (1.0 - self.config.bid_spread) - level_spread,
                                             price_decimals,
                                             false
                                         );
-                                        
+
                                         let ask_price = truncate_float(
-                                            self.current_mid_price + level_spread,
+                                            reference_mid * (1.0 + self.config.ask_spread) + level_spread,
                                             price_decimals,
                                             false
                                         );
@@ -483,209 +885,97 @@ impl EnhancedMarketMaker {
                                             level.level, bid_size, bid_price, ask_size, ask_price, 
                                             level.spread_multiplier, level.size_multiplier);
                                         
-                                        // Add the desired orders to our list
-                                        if bid_size > EPSILON {
-                                            desired_orders.push((true, bid_price, bid_size));
+                                        // Add the desired orders to our list, bounded per side so a
+                                        // large quote_levels config can't exceed the exchange's
+                                        // resting-order-per-side limit
+                                        let bid_count = desired_orders.iter().filter(|(is_bid, ..)| *is_bid).count();
+                                        let ask_count = desired_orders.iter().filter(|(is_bid, ..)| !*is_bid).count();
+
+                                        if bid_size > EPSILON && bid_count < MAX_NUM_LIMIT_ORDERS {
+                                            desired_orders.push((true, level.level, bid_price, bid_size));
                                         }
-                                        
-                                        if ask_size > EPSILON {
-                                            desired_orders.push((false, ask_price, ask_size));
+
+                                        if ask_size > EPSILON && ask_count < MAX_NUM_LIMIT_ORDERS {
+                                            desired_orders.push((false, level.level, ask_price, ask_size));
                                         }
                                     }
-                                    
+
                                     // Only continue if trading is enabled
-                                    if self.config.enable_trading {
-                                        // Get current active orders
+                                    if self.trading_enabled.load(Ordering::Relaxed) {
+                                        // Diff desired quotes against the currently resting orders
+                                        // (trigger markers excluded) into a minimal keep/cancel/place
+                                        // plan, then execute it atomically with rollback on failure
+                                        let desired: Vec<DesiredOrder> = desired_orders.iter()
+                                            .map(|(is_bid, level, price, size)| DesiredOrder { is_bid: *is_bid, level: *level, price: *price, size: *size })
+                                            .collect();
+
                                         let active_orders = active_orders_arc.lock().await.clone();
-                                        
-                                        // Separate orders for different operations
-                                        let mut orders_to_cancel: Vec<u64> = Vec::new();
-                                        let mut orders_to_place: Vec<ClientOrderRequest> = Vec::new();
-                                        
-                                        // Track order details for order placement
-                                        let mut order_details: Vec<(bool, f64, f64)> = Vec::new();
-                                        
-                                        // For each desired order, decide if we need to place new, modify existing, or keep as is
-                                        for (is_bid, price, size) in &desired_orders {
-                                            // Look for an existing order of the same side
-                                            let existing_order = active_orders.values()
-                                                .find(|o| o.is_bid == *is_bid);
-                                                
-                                            if let Some(order) = existing_order {
-                                                // Check if we should modify this order (price or size changed)
-                                                let price_changed = bps_diff(order.price, *price) > 2; // Allow 2 bps difference
-                                                let size_changed = (order.position - *size).abs() > EPSILON;
-                                                
-                                                if price_changed || size_changed {
-                                                    // Create a new order with the updated parameters
-                                                    // The order will cancel and replace the existing one
-                                                    let order_req = ClientOrderRequest {
-                                                        asset: self.config.symbol.clone(),
-                                                        is_buy: *is_bid,
-                                                        reduce_only: false,
-                                                        limit_px: *price,
-                                                        sz: *size,
-                                                        cloid: None,
-                                                        order_type: ClientOrder::Limit(ClientLimit {
-                                                            tif: "Alo".to_string(),
-                                                        }),
-                                                    };
-                                                    
-                                                    // Cancel the old order
-                                                    orders_to_cancel.push(order.oid);
-                                                    
-                                                    // Place the new order
-                                                    orders_to_place.push(order_req);
-                                                    order_details.push((*is_bid, *price, *size));
-                                                    
-                                                    info!("Will cancel and replace {} order: id={}, from {}@{} to {}@{}", 
-                                                        if *is_bid { "bid" } else { "ask" }, 
-                                                        order.oid, order.position, order.price, size, price);
-                                                } else {
-                                                    info!("Keeping existing {} order: id={}, {}@{}", 
-                                                        if *is_bid { "bid" } else { "ask" }, 
-                                                        order.oid, order.position, order.price);
-                                                }
-                                            } else {
-                                                // Create a new order
-                                                let order = ClientOrderRequest {
-                                                    asset: self.config.symbol.clone(),
-                                                    is_buy: *is_bid,
-                                                    reduce_only: false,
-                                                    limit_px: *price,
-                                                    sz: *size,
-                                                    cloid: None,
-                                                    order_type: ClientOrder::Limit(ClientLimit {
-                                                        tif: "Alo".to_string(),
-                                                    }),
-                                                };
-                                                
-                                                orders_to_place.push(order);
-                                                order_details.push((*is_bid, *price, *size));
-                                                info!("Will place new {} order: {}@{}", 
-                                                    if *is_bid { "bid" } else { "ask" }, size, price);
+                                        let plan = diff_orders(&desired, &active_orders, 2, EPSILON);
+
+                                        for oid in &plan.keep {
+                                            if let Some(order) = active_orders.get(oid) {
+                                                info!("Keeping existing {} order: id={}, {}@{}",
+                                                    if order.is_bid { "bid" } else { "ask" }, order.oid, order.position, order.price);
                                             }
                                         }
-                                        
-                                        // For each active order, if it's not in our desired orders, cancel it
-                                        for (oid, order) in &active_orders {
-                                            let order_still_needed = desired_orders.iter()
-                                                .any(|(is_bid, _, _)| *is_bid == order.is_bid);
-                                                
-                                            if !order_still_needed {
-                                                orders_to_cancel.push(*oid);
-                                                info!("Will cancel {} order: id={}, {}@{}", 
-                                                    if order.is_bid { "bid" } else { "ask" }, 
-                                                    order.oid, order.position, order.price);
+                                        for oid in &plan.cancel {
+                                            if let Some(order) = active_orders.get(oid) {
+                                                info!("Will cancel {} order: id={}, {}@{}",
+                                                    if order.is_bid { "bid" } else { "ask" }, order.oid, order.position, order.price);
                                             }
                                         }
-                                        
-                                        // Get the exchange client lock for operations
-                                        let exchange_client_lock = exchange_client_arc.lock().await;
-                                        
-                                        // 1. Execute cancellations if needed
-                                        if !orders_to_cancel.is_empty() {
-                                            let cancel_requests = orders_to_cancel.iter()
-                                                .map(|oid| ClientCancelRequest {
-                                                    asset: self.config.symbol.clone(),
-                                                    oid: *oid,
-                                                })
-                                                .collect::<Vec<_>>();
-                                                
-                                            match exchange_client_lock.bulk_cancel(cancel_requests, None).await {
-                                                Ok(_) => {
-                                                    info!("Successfully cancelled {} orders", orders_to_cancel.len());
-                                                    
-                                                    // Remove cancelled orders from the tracking map
-                                                    let mut active_orders_lock = active_orders_arc.lock().await;
-                                                    for oid in orders_to_cancel {
-                                                        active_orders_lock.remove(&oid);
-                                                    }
-                                                },
-                                                Err(e) => {
-                                                    warn!("Failed to cancel orders: {}", e);
-                                                }
-                                            }
+                                        for desired_order in &plan.place {
+                                            info!("Will place {} order: {}@{}",
+                                                if desired_order.is_bid { "bid" } else { "ask" }, desired_order.size, desired_order.price);
                                         }
-                                        
-                                        // 2. Place new orders (after cancellations)
-                                        if !orders_to_place.is_empty() {
-                                            // Log vault address status before placing orders
-                                            {
-                                                let client = exchange_client_lock.wallet.address();
-                                                let has_vault = exchange_client_lock.vault_address.is_some();
-                                                if has_vault {
-                                                    info!("Placing orders with wallet {} and VAULT ADDRESS: {:?}", 
-                                                         client, exchange_client_lock.vault_address);
-                                                } else {
-                                                    info!("Placing orders with wallet {} (NO VAULT ADDRESS)", client);
-                                                }
-                                            }
-                                            
-                                            match exchange_client_lock.bulk_order(orders_to_place, None).await {
-                                                Ok(response) => {
-                                                    if let ExchangeResponseStatus::Ok(ok_response) = response {
-                                                        if let Some(data) = ok_response.data {
-                                                            for (index, status) in data.statuses.into_iter().enumerate() {
-                                                                match status {
-                                                                    ExchangeDataStatus::Resting(order) => {
-                                                                        // Get a separate lock for updating orders
-                                                                        let mut active_orders_lock = active_orders_arc.lock().await;
-                                                                        
-                                                                        // Get the corresponding order details from the index
-                                                                        if let Some(&(is_bid, price, size)) = order_details.get(index) {
-                                                                            let order_type = if is_bid { "bid" } else { "ask" };
-                                                                        
-                                                                            active_orders_lock.insert(order.oid, EnhancedRestingOrder {
-                                                                                oid: order.oid,
-                                                                                position: size,
-                                                                                price,
-                                                                                is_bid,
-                                                                            });
-                                                                            
-                                                                            info!("Placed {} order: id={}, size={}, price={}, tif=Alo", 
-                                                                                order_type, order.oid, size, price);
-                                                                        } else {
-                                                                            warn!("Received order response with no matching details: {:?}", order);
-                                                                        }
-                                                                    },
-                                                                    _ => {warn!("Unknown order status: {:?}", status)},
-                                                                }
-                                                            }
-                                                        }
-                                                    } else {
-                                                        warn!("Bulk order placement failed: {:?}", response);
-                                                    }
-                                                },
-                                                Err(e) => {
-                                                    warn!("Failed to place bulk orders: {}", e);
-                                                }
+                                        for replacement in &plan.replace {
+                                            info!("Will replace order id={} with {} order: {}@{} (old order stays resting until the new one is confirmed)",
+                                                replacement.old_oid,
+                                                if replacement.new_order.is_bid { "bid" } else { "ask" },
+                                                replacement.new_order.size, replacement.new_order.price);
+                                        }
+
+                                        let exchange_client_lock = exchange_client_arc.lock().await;
+                                        if !plan.place.is_empty() || !plan.replace.is_empty() {
+                                            let client = exchange_client_lock.wallet.address();
+                                            let has_vault = exchange_client_lock.vault_address.is_some();
+                                            if has_vault {
+                                                info!("Placing orders with wallet {} and VAULT ADDRESS: {:?}",
+                                                     client, exchange_client_lock.vault_address);
+                                            } else {
+                                                info!("Placing orders with wallet {} (NO VAULT ADDRESS)", client);
                                             }
                                         }
+                                        execute_plan(&*exchange_client_lock, &active_orders_arc, &self.config.symbol, &plan).await;
                                     } else {
-                                        // If trading is disabled, just cancel all existing orders
+                                        // If trading is disabled, just cancel all existing quote orders;
+                                        // risk trigger markers stay so a risk exit can still fire
                                         let active_orders = active_orders_arc.lock().await.clone();
-                                        
-                                        if !active_orders.is_empty() {
-                                            let cancel_requests = active_orders.keys()
+                                        let quote_oids: Vec<u64> = active_orders.iter()
+                                            .filter(|(_, o)| o.trigger_kind.is_none())
+                                            .map(|(oid, _)| *oid)
+                                            .collect();
+
+                                        if !quote_oids.is_empty() {
+                                            let cancel_requests = quote_oids.iter()
                                                 .map(|oid| ClientCancelRequest {
                                                     asset: self.config.symbol.clone(),
                                                     oid: *oid,
                                                 })
                                                 .collect::<Vec<_>>();
-                                                
+
                                             let exchange_client_lock = exchange_client_arc.lock().await;
-                                            
+
                                             if let Err(e) = exchange_client_lock.bulk_cancel(cancel_requests, None).await {
                                                 warn!("Failed to cancel orders: {}", e);
                                             } else {
-                                                // Clear the order tracking map
+                                                // Clear the quote orders from the tracking map, keeping triggers
                                                 let mut active_orders_lock = active_orders_arc.lock().await;
-                                                active_orders_lock.clear();
-                                                info!("Trading is disabled - cancelled all {} existing orders", active_orders.len());
+                                                active_orders_lock.retain(|_, o| o.trigger_kind.is_some());
+                                                info!("Trading is disabled - cancelled all {} existing quote orders", quote_oids.len());
                                             }
                                         } else {
-                                            info!("Trading is disabled for {}. No orders to cancel.", self.config.symbol);
+                                            info!("Trading is disabled for {}. No quote orders to cancel.", self.config.symbol);
                                         }
                                     }
                                 }
@@ -693,6 +983,9 @@ impl EnhancedMarketMaker {
                         }
                     }
                 },
+                Message::UserFills(user_fills) => {
+                    self.apply_user_fills(&user_fills, &active_orders_arc).await;
+                },
                 // Handle other message types if needed
                 _ => {},
             }
@@ -704,6 +997,89 @@ impl EnhancedMarketMaker {
         Ok(())
     }
     
+    /// The next UTC timestamp (ms since epoch) at which the configured
+    /// session-expiry schedule will force a flatten, or `None` if no
+    /// schedule is configured. Exposed so operators/dashboards can see when
+    /// a forced flatten is coming without re-deriving the schedule.
+    pub fn next_session_expiry_ms(&self) -> Option<u64> {
+        let schedule = self.config.session_expiry.as_ref()?;
+        let now = current_timestamp_ms();
+        let period_ms = match schedule {
+            SessionExpirySchedule::Daily { .. } => DAY_MS,
+            SessionExpirySchedule::Weekly { .. } => 7 * DAY_MS,
+        };
+
+        let mut boundary = most_recent_session_boundary_ms(now, schedule);
+        if boundary <= now {
+            boundary += period_ms;
+        }
+        Some(boundary)
+    }
+
+    /// Force a flatten at the configured session-expiry boundary (daily or
+    /// weekly UTC schedule), independent of `check_risk_limits`'s
+    /// stop-loss/take-profit checks. Cancels all resting quote orders (risk
+    /// trigger markers aren't real exchange orders and are left alone),
+    /// flattens via `close_all_positions`, and resets the daily PnL counters
+    /// through `reset_daily_pnl_counters` - the same path
+    /// `check_and_reset_daily_pnl` uses. If `auto_rollover` is set, quoting
+    /// re-enables immediately for the next session; otherwise
+    /// `enable_trading` stays false until an operator re-enables it.
+    async fn check_session_expiry(
+        &mut self,
+        active_orders_arc: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+        exchange_client_arc: &Arc<Mutex<ExchangeClient>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let schedule = match self.config.session_expiry.clone() {
+            Some(schedule) => schedule,
+            None => return Ok(()),
+        };
+
+        let now = current_timestamp_ms();
+        let boundary = most_recent_session_boundary_ms(now, &schedule);
+
+        if self.session_start_timestamp >= boundary {
+            return Ok(());
+        }
+
+        warn!("Session expiry boundary crossed for {}. Forcing a flatten and halting quoting.", self.config.symbol);
+
+        let active_orders = active_orders_arc.lock().await.clone();
+        let quote_oids: Vec<u64> = active_orders.iter()
+            .filter(|(_, o)| o.trigger_kind.is_none())
+            .map(|(oid, _)| *oid)
+            .collect();
+
+        if !quote_oids.is_empty() {
+            let cancel_requests = quote_oids.iter()
+                .map(|oid| ClientCancelRequest { asset: self.config.symbol.clone(), oid: *oid })
+                .collect::<Vec<_>>();
+
+            let exchange_client_lock = exchange_client_arc.lock().await;
+            if let Err(e) = exchange_client_lock.bulk_cancel(cancel_requests, None).await {
+                warn!("Session expiry: failed to cancel orders for {}: {}", self.config.symbol, e);
+            } else {
+                drop(exchange_client_lock);
+                let mut active_orders_lock = active_orders_arc.lock().await;
+                active_orders_lock.retain(|_, o| o.trigger_kind.is_some());
+            }
+        }
+
+        self.close_all_positions().await?;
+        self.reset_daily_pnl_counters(boundary);
+        self.session_start_timestamp = boundary;
+
+        self.config.enable_trading = self.config.auto_rollover;
+        self.trading_enabled.store(self.config.enable_trading, Ordering::Relaxed);
+        if self.config.enable_trading {
+            info!("Session expiry: auto_rollover enabled, quoting resumes immediately for {}", self.config.symbol);
+        } else {
+            info!("Session expiry: quoting halted for {} until manually re-enabled", self.config.symbol);
+        }
+
+        Ok(())
+    }
+
     /// Check risk limits like stop loss and take profit
     /// Returns true if we should continue trading, false if we need to stop
     async fn check_risk_limits(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
@@ -763,6 +1139,43 @@ impl EnhancedMarketMaker {
         Ok(true)
     }
     
+    /// Cancel every resting *quote* order (trigger markers aren't real
+    /// exchange orders and are left alone); used on graceful shutdown so we
+    /// don't leave stale quotes resting after the process exits.
+    async fn cancel_resting_quote_orders(
+        &self,
+        exchange_client_arc: &Arc<Mutex<ExchangeClient>>,
+        active_orders_arc: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+    ) {
+        let active_orders = active_orders_arc.lock().await.clone();
+        let quote_oids: Vec<u64> = active_orders.iter()
+            .filter(|(_, o)| o.trigger_kind.is_none())
+            .map(|(oid, _)| *oid)
+            .collect();
+
+        if quote_oids.is_empty() {
+            return;
+        }
+
+        let cancel_requests = quote_oids.iter()
+            .map(|oid| ClientCancelRequest {
+                asset: self.config.symbol.clone(),
+                oid: *oid,
+            })
+            .collect::<Vec<_>>();
+
+        let exchange_client_lock = exchange_client_arc.lock().await;
+        if let Err(e) = exchange_client_lock.bulk_cancel(cancel_requests, None).await {
+            warn!("Failed to cancel resting quote orders for {} during shutdown: {}", self.config.symbol, e);
+            return;
+        }
+        drop(exchange_client_lock);
+
+        let mut active_orders_lock = active_orders_arc.lock().await;
+        active_orders_lock.retain(|_, o| o.trigger_kind.is_some());
+        info!("Cancelled all {} resting quote orders for {} during shutdown", quote_oids.len(), self.config.symbol);
+    }
+
     /// Close all positions for the symbol
     async fn close_all_positions(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Closing all positions for {}", self.config.symbol);
@@ -776,8 +1189,7 @@ impl EnhancedMarketMaker {
         
         let position = position_opt.unwrap();
         let raw_size = position.size;
-        let unrealized_pnl = position.unrealized_pnl;
-        
+
         // Create exchange client with proper initialization
         let base_url = if cfg!(feature = "testnet") { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
         
@@ -816,10 +1228,11 @@ impl EnhancedMarketMaker {
         
         match exchange_client.market_open(params).await {
             Ok(response) => {
+                // Don't credit realized PnL here: the closing order's fills
+                // arrive on the UserFills stream like any other fill and are
+                // credited there by `apply_user_fills`, same as
+                // `maintain_risk_trigger_orders`'s flatten.
                 info!("Position closed successfully: {:?}", response);
-                // Update realized PnL since we've closed the position
-                self.realized_daily_pnl += unrealized_pnl;
-                info!("Updated realized PnL: {}", self.realized_daily_pnl);
             },
             Err(e) => {
                 error!("Failed to close position: {}", e);
@@ -828,7 +1241,245 @@ impl EnhancedMarketMaker {
         
         Ok(())
     }
-    
+
+    /// Maintain resting stop-loss/take-profit trigger markers so real
+    /// price-threshold exits fire instead of relying purely on
+    /// `check_risk_limits` flattening on a PnL breach. The stop trigger
+    /// tracks `entry_price * (1 +/- trailing_stop_loss)` and the take-profit
+    /// trigger tracks `entry_price * (1 +/- trailing_take_profit)`, both
+    /// re-registered on every tick so they follow the entry price as it
+    /// moves. Neither trigger follows the `highest_pnl`/`lowest_pnl` extreme.
+    /// These markers share `active_orders` with real resting quote orders
+    /// but are never submitted to the exchange; they're independent of
+    /// `enable_trading` and the normal quote levels so a risk exit can still
+    /// fire while trading is toggled off.
+    async fn maintain_risk_trigger_orders(
+        &mut self,
+        active_orders_arc: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let position = match self.positions.get(&self.config.symbol) {
+            Some(p) if p.size.abs() > EPSILON => p.clone(),
+            _ => {
+                // Flat: no position to protect, drop any stale trigger markers
+                let mut active_orders = active_orders_arc.lock().await;
+                active_orders.retain(|_, o| o.trigger_kind.is_none());
+                return Ok(());
+            }
+        };
+
+        let is_long = position.size > 0.0;
+        let stop_price = if is_long {
+            position.entry_price * (1.0 - self.config.trailing_stop_loss)
+        } else {
+            position.entry_price * (1.0 + self.config.trailing_stop_loss)
+        };
+        let take_profit_price = if is_long {
+            position.entry_price * (1.0 + self.config.trailing_take_profit)
+        } else {
+            position.entry_price * (1.0 - self.config.trailing_take_profit)
+        };
+
+        // Re-register the trigger markers so they always track the latest
+        // entry price, replacing whatever was registered before
+        {
+            let mut active_orders = active_orders_arc.lock().await;
+            active_orders.retain(|_, o| o.trigger_kind.is_none());
+
+            let stop_oid = next_trigger_oid();
+            active_orders.insert(stop_oid, EnhancedRestingOrder {
+                oid: stop_oid,
+                position: position.size.abs(),
+                price: stop_price,
+                is_bid: !is_long, // flattening a long is a sell, a short is a buy
+                level: 0,
+                trigger_kind: Some(TriggerKind::StopLoss),
+                original_size: position.size.abs(),
+                filled_size: 0.0,
+            });
+
+            let tp_oid = next_trigger_oid();
+            active_orders.insert(tp_oid, EnhancedRestingOrder {
+                oid: tp_oid,
+                position: position.size.abs(),
+                price: take_profit_price,
+                is_bid: !is_long,
+                level: 0,
+                trigger_kind: Some(TriggerKind::TakeProfit),
+                original_size: position.size.abs(),
+                filled_size: 0.0,
+            });
+        }
+
+        // Check if the current mid has crossed either trigger
+        let triggered = {
+            let active_orders = active_orders_arc.lock().await;
+            active_orders.values()
+                .find(|o| match o.trigger_kind {
+                    Some(TriggerKind::StopLoss) =>
+                        if is_long { self.current_mid_price <= o.price } else { self.current_mid_price >= o.price },
+                    Some(TriggerKind::TakeProfit) =>
+                        if is_long { self.current_mid_price >= o.price } else { self.current_mid_price <= o.price },
+                    None => false,
+                })
+                .cloned()
+        };
+
+        if let Some(order) = triggered {
+            warn!("{:?} trigger hit for {} at mid {} (trigger price {}). Flattening {}.",
+                order.trigger_kind, self.config.symbol, self.current_mid_price, order.price, order.position);
+
+            let base_url = if cfg!(feature = "testnet") { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
+            let wallet = self.market_maker.exchange_client.wallet.clone();
+            let exchange_client = ExchangeClient::new(None, wallet, base_url, None, None).await?;
+
+            let size_decimals = self.get_size_decimals_for_symbol(&self.config.symbol);
+            let truncated_size = truncate_float(order.position, size_decimals, false);
+
+            let params = MarketOrderParams {
+                asset: &self.config.symbol,
+                is_buy: order.is_bid,
+                sz: truncated_size,
+                px: None,
+                slippage: Some(0.03),
+                cloid: None,
+                wallet: None,
+            };
+
+            match exchange_client.market_open(params).await {
+                Ok(response) => {
+                    info!("Risk trigger order executed: {:?}", response);
+                },
+                Err(e) => {
+                    error!("Failed to execute risk trigger order: {}", e);
+                }
+            }
+
+            let mut active_orders = active_orders_arc.lock().await;
+            active_orders.retain(|_, o| o.trigger_kind.is_none());
+        }
+
+        Ok(())
+    }
+
+    /// Aggregate fills from the user-fills stream against the resting order
+    /// they were matched from, keyed by `oid`, so `remaining()` reflects
+    /// partial fills instead of assuming an order is all-or-nothing. Realized
+    /// PnL is credited per partial fill using the same weighted-average cost
+    /// basis as `PositionManager::apply_fill`, rather than only when
+    /// `update_position_from_user_state` later observes the size reduction.
+    ///
+    /// Every (re)subscribe delivers an initial snapshot of recent fills
+    /// (`is_snapshot: true`) that we've already accounted for - crediting it
+    /// again would double-count on top of the `realized_daily_pnl` chunk4-4
+    /// restores from Redis on startup, so the whole batch is skipped. Live
+    /// fills are further deduped by `tid` against `seen_fill_ids`, since a WS
+    /// reconnect can redeliver one we already credited without flagging it
+    /// as part of a snapshot.
+    async fn apply_user_fills(
+        &mut self,
+        user_fills: &UserFills,
+        active_orders_arc: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+    ) {
+        if user_fills.data.is_snapshot.unwrap_or(false) {
+            debug!("Skipping is_snapshot user-fills batch for {} ({} fills already accounted for)",
+                self.config.symbol, user_fills.data.fills.len());
+            return;
+        }
+
+        for fill in &user_fills.data.fills {
+            if fill.coin != self.config.symbol {
+                continue;
+            }
+
+            if !self.seen_fill_ids.insert(fill.tid) {
+                debug!("Skipping already-credited fill tid={} for {}", fill.tid, self.config.symbol);
+                continue;
+            }
+            self.fill_ids_order.push_back(fill.tid);
+            if self.fill_ids_order.len() > MAX_TRACKED_FILL_IDS {
+                if let Some(evicted) = self.fill_ids_order.pop_front() {
+                    self.seen_fill_ids.remove(&evicted);
+                }
+            }
+
+            let (fill_size, fill_price) = match (fill.sz.parse::<f64>(), fill.px.parse::<f64>()) {
+                (Ok(sz), Ok(px)) => (sz, px),
+                _ => {
+                    warn!("Failed to parse fill sz/px for {}: {:?}", self.config.symbol, fill);
+                    continue;
+                }
+            };
+
+            let is_buy = fill.side == "B";
+            let mut active_orders = active_orders_arc.lock().await;
+            if let Some(order) = active_orders.get_mut(&fill.oid) {
+                order.filled_size += fill_size;
+                if order.remaining() < EPSILON {
+                    active_orders.remove(&fill.oid);
+                }
+            }
+            drop(active_orders);
+
+            // Credit realized PnL incrementally for this partial fill, and
+            // roll the fill into position.size/entry_price using the same
+            // volume-weighted average cost basis as PositionManager::apply_fill,
+            // so a second fill in the same batch isn't computed against a
+            // size/entry_price that's already stale by the time it runs.
+            let signed_fill_qty = if is_buy { fill_size.abs() } else { -fill_size.abs() };
+            let mut realized = 0.0;
+
+            if let Some(position) = self.positions.get_mut(&self.config.symbol) {
+                let previous_size = position.size;
+
+                let new_size = if previous_size == 0.0 || previous_size.signum() == signed_fill_qty.signum() {
+                    // Opening or adding to the position: roll into the
+                    // volume-weighted average entry price, no PnL realized.
+                    let new_size = previous_size + signed_fill_qty;
+                    position.entry_price = (position.entry_price * previous_size.abs() + fill_price * signed_fill_qty.abs())
+                        / new_size.abs();
+                    new_size
+                } else {
+                    // Opposite direction: realize PnL on whatever quantity
+                    // this fill closes.
+                    let closed_qty = signed_fill_qty.abs().min(previous_size.abs());
+                    realized = (fill_price - position.entry_price) * closed_qty * previous_size.signum();
+                    position.realized_pnl += realized;
+                    self.realized_daily_pnl += realized;
+
+                    let new_size = previous_size + signed_fill_qty;
+                    if new_size.abs() < EPSILON {
+                        position.entry_price = 0.0;
+                        0.0
+                    } else if new_size.signum() != previous_size.signum() {
+                        // Flipped through zero: the remainder opens a new
+                        // lot at the fill price.
+                        position.entry_price = fill_price;
+                        new_size
+                    } else {
+                        new_size
+                    }
+                };
+
+                position.size = new_size;
+
+                if realized != 0.0 {
+                    info!("Partial fill on {} oid={}: realized {} (total realized_daily_pnl {})",
+                        self.config.symbol, fill.oid, realized, self.realized_daily_pnl);
+                    self.persist_pnl_state();
+                }
+            }
+
+            self.publish_position_update(&self.config.symbol.clone(), PositionDelta {
+                filled_qty: signed_fill_qty,
+                fill_price: Some(fill_price),
+                realized_pnl_delta: realized,
+                description: format!("fill oid={} {} {}@{}", fill.oid, if is_buy { "buy" } else { "sell" }, fill_size, fill_price),
+            });
+
+            self.ingest_fill_candle_tick(fill.time, fill_price, fill_size).await;
+        }
+    }
+
     /// Update position from user state
     async fn update_position_from_user_state(&mut self, user_state: &UserStateResponse, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Check if we previously had a position that's now closed
@@ -857,20 +1508,19 @@ impl EnhancedMarketMaker {
                 // Update position
                 let notional_usd = self.current_mid_price * size.abs();
                 
-                // Check if position was reduced (partial close)
+                // Check if position was reduced (partial close). `apply_user_fills`
+                // already credits `realized_daily_pnl` incrementally as each fill
+                // lands on the user-fills stream, so this poll-driven snapshot
+                // only needs to log the observation, not re-credit it - doing
+                // both would double-count the same close.
                 if had_position && previous_size.abs() > size.abs() {
-                    // Calculate the portion of the position that was closed
-                    let closed_portion = (previous_size.abs() - size.abs()) / previous_size.abs();
-                    let previous_pnl = self.positions.get(symbol).map(|p| p.unrealized_pnl).unwrap_or(0.0);
-                    
-                    // Add the realized portion to daily realized PnL
-                    let realized_portion = previous_pnl * closed_portion;
-                    self.realized_daily_pnl += realized_portion;
-                    
-                    info!("Position partially closed. Realized PnL: {}, Total realized: {}", 
-                        realized_portion, self.realized_daily_pnl);
+                    info!("Position size reduced for {}: {} -> {} (realized PnL already credited per-fill)",
+                        symbol, previous_size, size);
                 }
                 
+                let previous_version = self.positions.get(symbol).map(|p| p.version).unwrap_or(0);
+                let realized_pnl = self.positions.get(symbol).map(|p| p.realized_pnl).unwrap_or(0.0);
+
                 let position = Position {
                     symbol: symbol.to_string(),
                     size,
@@ -878,8 +1528,11 @@ impl EnhancedMarketMaker {
                     current_price: self.current_mid_price,
                     unrealized_pnl,
                     notional_usd,
+                    version: previous_version + 1,
+                    last_update_ts: current_timestamp_ms(),
+                    realized_pnl,
                 };
-                
+
                 self.positions.insert(symbol.to_string(), position);
                 
                 // Update highest/lowest PnL if needed
@@ -889,26 +1542,37 @@ impl EnhancedMarketMaker {
                 if self.highest_pnl == 0.0 || total_pnl > self.highest_pnl {
                     self.highest_pnl = total_pnl;
                 }
-                
+
                 if self.lowest_pnl == 0.0 || total_pnl < self.lowest_pnl {
                     self.lowest_pnl = total_pnl;
                 }
+                self.persist_pnl_state();
+
+                self.publish_position_update(symbol, PositionDelta {
+                    filled_qty: size - previous_size,
+                    fill_price: None,
+                    realized_pnl_delta: 0.0,
+                    description: format!("user-state poll for {}: size {} -> {}", symbol, previous_size, size),
+                });
             }
         }
-        
-        // If we had a position but didn't find it now, it must have been fully closed
+
+        // If we had a position but didn't find it now, it must have been fully
+        // closed. Realized PnL for the closing fill(s) was already credited by
+        // `apply_user_fills` as they arrived, so just drop the stale tracking
+        // entry rather than crediting it again from the last-seen unrealized PnL.
         if had_position && !found_position {
-            // Get the previous position's PnL and add it to realized
-            let previous_pnl = self.positions.get(symbol).map(|p| p.unrealized_pnl).unwrap_or(0.0);
-            self.realized_daily_pnl += previous_pnl;
-            
-            // Remove the position from our tracking
             self.positions.remove(symbol);
-            
-            info!("Position fully closed. Realized PnL: {}, Total realized: {}", 
-                previous_pnl, self.realized_daily_pnl);
+            info!("Position fully closed for {}. Total realized: {}", symbol, self.realized_daily_pnl);
+
+            self.publish_position_update(symbol, PositionDelta {
+                filled_qty: -previous_size,
+                fill_price: None,
+                realized_pnl_delta: 0.0,
+                description: format!("user-state poll for {}: position fully closed", symbol),
+            });
         }
-        
+
         Ok(())
     }
     
@@ -952,82 +1616,201 @@ impl EnhancedMarketMaker {
     fn get_total_unrealized_pnl(&self) -> f64 {
         self.positions.values().map(|p| p.unrealized_pnl).sum()
     }
+
+    /// Fire-and-forget persist of the current daily PnL counters to Redis,
+    /// so `new_with_reference_price_source` can resume the same trailing
+    /// baselines after a restart instead of silently zeroing the stop-loss
+    /// and take-profit state. Called from `apply_user_fills`,
+    /// `update_position_from_user_state`, `reset_daily_pnl_counters` and on
+    /// shutdown; the `highest_pnl`/`lowest_pnl` updates made in
+    /// `check_risk_limits` are not persisted there and reach Redis with the
+    /// next of those calls.
+    fn persist_pnl_state(&self) {
+        let redis_pool_clone = self.redis_pool.clone();
+        let symbol = self.config.symbol.clone();
+        let state = (self.daily_pnl, self.realized_daily_pnl, self.highest_pnl, self.lowest_pnl, self.day_start_timestamp);
+        tokio::spawn(async move {
+            if let Err(e) = store_pnl_state_in_redis(&redis_pool_clone, &symbol, state).await {
+                error!("Failed to persist PnL state to Redis: {}", e);
+            }
+        });
+    }
+
+    /// Publish a `PositionUpdateEvent` for `symbol` carrying both `delta`
+    /// (what just changed) and a snapshot of the full reference state, so
+    /// subscribers can reason on either without replaying history. A send
+    /// error just means nobody is currently subscribed, which is fine.
+    fn publish_position_update(&self, symbol: &str, delta: PositionDelta) {
+        let position = self.positions.get(symbol);
+        let snapshot = PositionSnapshot {
+            size: position.map(|p| p.size).unwrap_or(0.0),
+            entry_price: position.map(|p| p.entry_price).unwrap_or(0.0),
+            unrealized_pnl: position.map(|p| p.unrealized_pnl).unwrap_or(0.0),
+            realized_pnl: position.map(|p| p.realized_pnl).unwrap_or(0.0),
+            realized_daily_pnl: self.realized_daily_pnl,
+            highest_pnl: self.highest_pnl,
+            lowest_pnl: self.lowest_pnl,
+        };
+
+        let _ = self.position_events.send(PositionUpdateEvent {
+            symbol: symbol.to_string(),
+            timestamp_ms: current_timestamp_ms(),
+            delta,
+            snapshot,
+        });
+    }
     
-    /// Check if we need to reset daily PnL (new trading day)
+    /// Check if we've crossed `rollover_utc_hour` for a new calendar day and,
+    /// if so, snapshot and reset the daily PnL counters. Rolls over at the
+    /// next UTC boundary crossing rather than 24h after `day_start_timestamp`,
+    /// so a process started mid-day still rolls over at the right time.
     fn check_and_reset_daily_pnl(&mut self) {
         let now = current_timestamp_ms();
-        
-        // Check if it's a new day (86,400,000 ms = 24 hours)
-        if now - self.day_start_timestamp > 86_400_000 {
-            // Reset for new trading day
-            info!("New trading day started. Resetting daily PnL tracking.");
-            info!("Previous day's final PnL: {} (Realized: {}, Unrealized: {})",
-                self.daily_pnl, self.realized_daily_pnl, self.get_total_unrealized_pnl());
-                
-            self.realized_daily_pnl = 0.0;
-            self.highest_pnl = 0.0;
-            self.lowest_pnl = 0.0;
-            self.day_start_timestamp = now;
+        let boundary = most_recent_rollover_boundary_ms(now, self.config.rollover_utc_hour);
+
+        if self.day_start_timestamp < boundary {
+            info!("Daily rollover boundary ({}:00 UTC) crossed. Resetting daily PnL tracking.",
+                self.config.rollover_utc_hour);
+            self.reset_daily_pnl_counters(boundary);
         }
     }
+
+    /// Snapshot the day's PnL stats to Redis and reset the daily counters to
+    /// start tracking from `boundary`. Shared by `check_and_reset_daily_pnl`
+    /// (calendar-day rollover) and `check_session_expiry` (session-schedule
+    /// flatten), so both paths leave the bookkeeping in the same state.
+    ///
+    /// A position left open across the boundary still carries its
+    /// unrealized PnL into the new session, so `highest_pnl`/`lowest_pnl`
+    /// are seeded from that unrealized PnL rather than zero - otherwise the
+    /// trailing stop-loss/take-profit baselines would start from a false
+    /// "nothing has happened yet" state and mis-trigger on the first tick.
+    fn reset_daily_pnl_counters(&mut self, boundary: u64) {
+        info!("Previous period's final PnL: {} (Realized: {}, Unrealized: {})",
+            self.daily_pnl, self.realized_daily_pnl, self.get_total_unrealized_pnl());
+
+        let redis_pool_clone = self.redis_pool.clone();
+        let symbol = self.config.symbol.clone();
+        let snapshot = (self.daily_pnl, self.realized_daily_pnl, self.highest_pnl, self.lowest_pnl, self.day_start_timestamp);
+        tokio::spawn(async move {
+            if let Err(e) = persist_daily_pnl_snapshot(&redis_pool_clone, &symbol, snapshot).await {
+                error!("Failed to persist daily PnL snapshot to Redis: {}", e);
+            }
+        });
+
+        let carried_unrealized = self.get_total_unrealized_pnl();
+        self.daily_pnl = 0.0;
+        self.realized_daily_pnl = 0.0;
+        self.highest_pnl = carried_unrealized.max(0.0);
+        self.lowest_pnl = carried_unrealized.min(0.0);
+        self.day_start_timestamp = boundary;
+        self.persist_pnl_state();
+    }
     
     /// Update market maker configuration
-    pub fn update_config(&mut self, config: MarketMakerConfig) {
+    /// Apply `config` locally and persist it to Redis under the
+    /// optimistic-concurrency guard in `store_config_in_redis`. `config` is
+    /// stamped with the version this instance last saw (`self.config.version`)
+    /// before the write, so a conflicting write from another operator/instance
+    /// surfaces as an `Err` here instead of being silently clobbered; on
+    /// conflict, local state is left unchanged so this instance keeps
+    /// quoting off whatever was last successfully applied.
+    pub async fn update_config(&mut self, config: MarketMakerConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Updating configuration for {}", config.symbol);
-        info!("New params - daily_return_bps: {}, notional_per_side: {}, interval: {}", 
+        info!("New params - daily_return_bps: {}, notional_per_side: {}, interval: {}",
             config.daily_return_bps, config.notional_per_side, config.force_quote_refresh_interval);
-        
+
         // Log old vs new values for key parameters
-        info!("Config CHANGE - daily_return_bps: {} -> {}, notional_per_side: {} -> {}", 
+        info!("Config CHANGE - daily_return_bps: {} -> {}, notional_per_side: {} -> {}",
             self.config.daily_return_bps, config.daily_return_bps,
             self.config.notional_per_side, config.notional_per_side);
-        
-        // Store the new configuration
-        self.config = config.clone();
-        
+
+        let mut config_to_store = config.clone();
+        config_to_store.version = self.config.version;
+        store_config_in_redis(&self.redis_pool, &config_to_store).await?;
+
+        // Store the new configuration, with the version `store_config_in_redis`
+        // just bumped it to
+        let mut config = config;
+        config.version = config_to_store.version.wrapping_add(1);
+        self.config = config;
+        self.trading_enabled.store(self.config.enable_trading, Ordering::Relaxed);
+
         // Update the underlying market maker parameters
         // Get the correct price precision for this symbol
         let price_decimals = self.get_price_decimals_for_symbol(&self.config.symbol);
-        
+
         // Update the market maker's parameters
         self.market_maker.half_spread = self.config.daily_return_bps / 365;
         self.market_maker.target_liquidity = self.config.notional_per_side;
         self.market_maker.max_absolute_position_size = calculate_max_position(&self.config);
         self.market_maker.decimals = price_decimals;
-        
-        info!("Market maker parameters updated - half_spread: {}, target_liquidity: {}, max_position: {}, decimals: {}", 
-            self.market_maker.half_spread, self.market_maker.target_liquidity, 
+
+        info!("Market maker parameters updated - half_spread: {}, target_liquidity: {}, max_position: {}, decimals: {}",
+            self.market_maker.half_spread, self.market_maker.target_liquidity,
             self.market_maker.max_absolute_position_size, self.market_maker.decimals);
-            
+
         // Force quote refresh on next iteration
         self.last_quote_time = 0;
-        
-        // Store the updated config in Redis to ensure it's available to other instances
-        let redis_clone = self.redis_client.clone();
-        let config_clone = config;
-        tokio::spawn(async move {
-            if let Err(e) = store_config_in_redis(&redis_clone, &config_clone).await {
-                error!("Failed to store configuration in Redis: {}", e);
-            }
-        });
+
+        Ok(())
+    }
+
+    /// Flip `enable_trading` on the live instance without persisting to
+    /// Redis or bumping `config.version`, for an operator `PauseSymbol`/
+    /// `ResumeSymbol` call against the control server. Only touches the
+    /// `trading_enabled` flag the quoting loop reads each tick, not
+    /// `self.config`, so it takes no lock the running task could be holding.
+    /// A config update that later arrives through the normal path
+    /// (`apply_config_from_redis`, `update_config`) still wins, since it
+    /// resyncs `trading_enabled` when it replaces `self.config` wholesale.
+    pub fn set_enable_trading(&self, enabled: bool) {
+        info!("{}: {} trading via control server", self.config.symbol, if enabled { "resuming" } else { "pausing" });
+        self.trading_enabled.store(enabled, Ordering::Relaxed);
     }
 
     /// Get the appropriate price precision (number of decimal places) for a symbol
     fn get_price_decimals_for_symbol(&self, symbol: &str) -> u32 {
-        // Different assets have different price precision requirements
-        // These are typically standardized by the exchange
-        match symbol {
-            "BTC" => 0,  // Bitcoin typically uses 2 decimal places for price ($xx,xxx.xx)
-            "ETH" => 2,  // Ethereum also 2 decimals
-            "SOL" => 3,  // Solana with 3
-            "AVAX" => 3,
-            "MATIC" => 4,
-            "DOGE" => 6,
-            "SHIB" => 8, // Very low priced assets need more precision
-            // Add more symbols as needed
-            _ => 2,      // Default to 2 decimal places for other assets
+        price_decimals_for_symbol(self.asset_meta.as_ref(), symbol)
+    }
+}
+
+/// Derive a perp's price precision (number of decimal places) from the
+/// exchange's asset metadata: Hyperliquid's tick rule is
+/// `price_decimals = MAX_DECIMALS - sz_decimals` (`MAX_DECIMALS` is 6 for
+/// perps, 8 for spot), further capped at 5 decimal places. The cap is on
+/// decimal places only: the number of significant figures a price carries
+/// is not limited here and grows with its integer digits. Falls back to a hardcoded per-symbol table only
+/// when `asset_meta` is `None` or doesn't list `symbol`, so `EnhancedMarketMaker::new`
+/// (before `self` exists), `get_price_decimals_for_symbol`, and
+/// `slow_path_check_config`'s Redis-driven refresh all agree.
+fn price_decimals_for_symbol(asset_meta: Option<&Meta>, symbol: &str) -> u32 {
+    const MAX_DECIMALS_PERP: u32 = 6;
+    const MAX_SIGNIFICANT_FIGURES: u32 = 5;
+
+    if let Some(meta) = asset_meta {
+        if let Some(asset) = meta.universe.iter().find(|a| a.name == symbol) {
+            let decimals = MAX_DECIMALS_PERP.saturating_sub(asset.sz_decimals).min(MAX_SIGNIFICANT_FIGURES);
+            debug!("Using API-derived price precision for {}: {} decimals (sz_decimals={})", symbol, decimals, asset.sz_decimals);
+            return decimals;
         }
     }
+
+    // Fallback to hardcoded values in case we couldn't get the precision from API
+    let fallback_precision = match symbol {
+        "BTC" => 0,  // Bitcoin quoted in whole dollars ($xx,xxx)
+        "ETH" => 2,  // Ethereum also 2 decimals
+        "SOL" => 3,  // Solana with 3
+        "AVAX" => 3,
+        "MATIC" => 4,
+        "DOGE" => 6,
+        "SHIB" => 8, // Very low priced assets need more precision
+        // Add more symbols as needed
+        _ => 2,      // Default to 2 decimal places for other assets
+    };
+
+    debug!("Using fallback price precision for {}: {} decimals (API data not available)", symbol, fallback_precision);
+    fallback_precision
 }
 
 /// Calculate the maximum position size based on configuration
@@ -1050,9 +1833,136 @@ fn current_timestamp_ms() -> u64 {
         .as_millis() as u64
 }
 
-/// Slow path function to check Redis for config updates
+const DAY_MS: u64 = 86_400_000;
+const HOUR_MS: u64 = 3_600_000;
+
+/// Most recent UTC timestamp (ms since epoch) at which `rollover_utc_hour`
+/// was crossed, at or before `now`. Since the unix epoch is UTC-aligned,
+/// `now % 1 day` is exactly the time-of-day in UTC, so this needs no
+/// timezone-aware date library.
+fn most_recent_rollover_boundary_ms(now: u64, rollover_utc_hour: u32) -> u64 {
+    let today_midnight = now - (now % DAY_MS);
+    let boundary = today_midnight + (rollover_utc_hour as u64 % 24) * HOUR_MS;
+
+    if boundary <= now {
+        boundary
+    } else {
+        boundary - DAY_MS
+    }
+}
+
+/// Most recent UTC timestamp (ms since epoch) at which `schedule`'s boundary
+/// was crossed, at or before `now`. For `Weekly`, walks back from the most
+/// recent daily `utc_hour` crossing to the most recent matching weekday
+/// (Jan 1 1970 was a Thursday, so `(epoch_day + 4) % 7` gives weekday with
+/// Sunday = 0, again needing no timezone-aware date library).
+fn most_recent_session_boundary_ms(now: u64, schedule: &SessionExpirySchedule) -> u64 {
+    match schedule {
+        SessionExpirySchedule::Daily { utc_hour } => most_recent_rollover_boundary_ms(now, *utc_hour),
+        SessionExpirySchedule::Weekly { utc_weekday, utc_hour } => {
+            let mut boundary = most_recent_rollover_boundary_ms(now, *utc_hour);
+            loop {
+                let weekday = ((boundary / DAY_MS + 4) % 7) as u32;
+                if weekday == utc_weekday % 7 {
+                    return boundary;
+                }
+                boundary -= DAY_MS;
+            }
+        }
+    }
+}
+
+/// Snapshot of the day's PnL stats at rollover: (daily_pnl, realized_daily_pnl, highest_pnl, lowest_pnl, day_start_timestamp)
+type DailyPnlSnapshot = (f64, f64, f64, f64, u64);
+
+/// Persist a completed day's PnL snapshot to Redis before the counters reset
+async fn persist_daily_pnl_snapshot(
+    redis_pool: &RedisPool,
+    symbol: &str,
+    (daily_pnl, realized_daily_pnl, highest_pnl, lowest_pnl, day_start_timestamp): DailyPnlSnapshot,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let snapshot_json = serde_json::json!({
+        "symbol": symbol,
+        "daily_pnl": daily_pnl,
+        "realized_daily_pnl": realized_daily_pnl,
+        "highest_pnl": highest_pnl,
+        "lowest_pnl": lowest_pnl,
+        "day_start_timestamp": day_start_timestamp,
+    }).to_string();
+
+    let mut conn = redis_pool.get().await?;
+    let key = format!("daily_pnl_snapshot:{}:{}", symbol, day_start_timestamp);
+    conn.set::<_, _, ()>(key, &snapshot_json).await?;
+
+    debug!("Persisted daily PnL snapshot to Redis for {}: {}", symbol, snapshot_json);
+
+    Ok(())
+}
+
+/// Redis key holding the live (not-yet-rolled-over) PnL counters for a
+/// symbol, distinct from `daily_pnl_snapshot:{symbol}:{day_start_timestamp}`
+/// which archives a completed day rather than tracking the current one.
+fn pnl_state_key(symbol: &str) -> String {
+    format!("pnl:{}", symbol)
+}
+
+/// Persist the live daily PnL counters - the same shape as
+/// `DailyPnlSnapshot` - so a restart can resume mid-session instead of
+/// resetting the trailing stop-loss/take-profit baselines to zero.
+async fn store_pnl_state_in_redis(
+    redis_pool: &RedisPool,
+    symbol: &str,
+    (daily_pnl, realized_daily_pnl, highest_pnl, lowest_pnl, day_start_timestamp): DailyPnlSnapshot,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let state_json = serde_json::json!({
+        "daily_pnl": daily_pnl,
+        "realized_daily_pnl": realized_daily_pnl,
+        "highest_pnl": highest_pnl,
+        "lowest_pnl": lowest_pnl,
+        "day_start_timestamp": day_start_timestamp,
+    }).to_string();
+
+    let mut conn = redis_pool.get().await?;
+    conn.set::<_, _, ()>(pnl_state_key(symbol), &state_json).await?;
+
+    Ok(())
+}
+
+/// Load the live daily PnL counters persisted by `store_pnl_state_in_redis`,
+/// if any. `check_and_reset_daily_pnl`'s next poll already rolls these over
+/// correctly if `day_start_timestamp` turns out to be stale (e.g. the
+/// process was down across a rollover boundary), so this can load
+/// unconditionally without duplicating that boundary logic here.
+async fn load_pnl_state_from_redis(
+    redis_pool: &RedisPool,
+    symbol: &str,
+) -> Result<Option<DailyPnlSnapshot>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut conn = redis_pool.get().await?;
+    let key = pnl_state_key(symbol);
+
+    if !conn.exists::<_, bool>(&key).await? {
+        return Ok(None);
+    }
+
+    let state_json: String = conn.get(&key).await?;
+    let value: serde_json::Value = serde_json::from_str(&state_json)?;
+
+    let daily_pnl = value["daily_pnl"].as_f64().unwrap_or(0.0);
+    let realized_daily_pnl = value["realized_daily_pnl"].as_f64().unwrap_or(0.0);
+    let highest_pnl = value["highest_pnl"].as_f64().unwrap_or(0.0);
+    let lowest_pnl = value["lowest_pnl"].as_f64().unwrap_or(0.0);
+    let day_start_timestamp = value["day_start_timestamp"].as_u64().unwrap_or_else(current_timestamp_ms);
+
+    Ok(Some((daily_pnl, realized_daily_pnl, highest_pnl, lowest_pnl, day_start_timestamp)))
+}
+
+/// Slow path function to check Redis for config updates. Kept as a
+/// low-frequency reconciliation fallback alongside the keyspace-notification
+/// subscription in `subscribe_config_keyspace_events`, in case a
+/// notification is ever missed (e.g. the subscriber's connection was
+/// reconnecting when the `SET` happened).
 async fn slow_path_check_config(
-    redis_client: &RedisClient,
+    redis_pool: &RedisPool,
     symbol: &str,
     params: Arc<Mutex<SharedMarketMakerParams>>,
     exchange_client: &Arc<Mutex<ExchangeClient>>,
@@ -1060,203 +1970,266 @@ async fn slow_path_check_config(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Create a key for Redis lookup
     let key = format!("config:{}", symbol);
-    
+
     // Connect to Redis
-    let mut conn = redis_client.get_async_connection().await?;
-    
+    let mut conn = redis_pool.get().await?;
+
     // Check if config exists
     if !conn.exists::<_, bool>(&key).await? {
         debug!("No config found in Redis for {}", symbol);
         return Ok(());
     }
-    
+
     // Read the config JSON from Redis
     let config_json: String = conn.get(&key).await?;
-    
+
+    apply_config_from_redis(&config_json, symbol, &params, exchange_client, active_orders, "[SLOW PATH]").await
+}
+
+/// Subscribe to keyspace notifications on `config:{symbol}` so a `SET`
+/// triggers an immediate reload instead of waiting for the next slow-path
+/// poll. Requires the Redis server to have `notify-keyspace-events`
+/// including `K` (keyspace events) and `$`/`A` (string commands) enabled -
+/// this issues `CONFIG SET notify-keyspace-events KEA` on startup as a
+/// best-effort default, but operators running a managed/locked-down Redis
+/// may need to enable it out of band instead.
+async fn subscribe_config_keyspace_events(
+    redis_pool: RedisPool,
+    redis_client: RedisClient,
+    symbol: String,
+    params: Arc<Mutex<SharedMarketMakerParams>>,
+    exchange_client: Arc<Mutex<ExchangeClient>>,
+    active_orders: Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+) {
+    if let Ok(mut conn) = redis_pool.get().await {
+        let result: redis::RedisResult<()> = redis::cmd("CONFIG")
+            .arg("SET").arg("notify-keyspace-events").arg("KEA")
+            .query_async(&mut conn).await;
+        if let Err(e) = result {
+            warn!("Could not enable notify-keyspace-events (may require operator to enable it out of band): {}", e);
+        }
+    }
+
+    let pattern = format!("__keyspace@0__:config:{}", symbol);
+    let pubsub = crate::resilient_pubsub::ResilientPubSub::psubscribe(redis_client, pattern.clone());
+    info!("Subscribed to config keyspace notifications on {}", pattern);
+
+    loop {
+        let event = pubsub.recv().await;
+        debug!("Config keyspace event for {}: {}", symbol, event);
+
+        let key = format!("config:{}", symbol);
+        let config_json: String = match redis_pool.get().await {
+            Ok(mut conn) => match conn.get(&key).await {
+                Ok(json) => json,
+                Err(e) => {
+                    warn!("Config keyspace notification fired for {} but GET failed: {}", symbol, e);
+                    continue;
+                }
+            },
+            Err(e) => {
+                warn!("Config keyspace notification fired for {} but Redis connect failed: {}", symbol, e);
+                continue;
+            }
+        };
+
+        if let Err(e) = apply_config_from_redis(&config_json, &symbol, &params, &exchange_client, &active_orders, "[KEYSPACE EVENT]").await {
+            error!("Failed to apply config from keyspace notification for {}: {}", symbol, e);
+        }
+    }
+}
+
+/// Diff-and-apply body shared by the slow-path poll and the keyspace-event
+/// subscriber: parses `config_json`, and if it differs from the currently
+/// shared config, updates `params`, recreates the `ExchangeClient` if the
+/// vault address changed, and cancels resting quote orders so they're
+/// re-quoted under the new parameters. `log_prefix` tags log lines with
+/// which path triggered the reload (e.g. "[SLOW PATH]" vs
+/// "[KEYSPACE EVENT]").
+async fn apply_config_from_redis(
+    config_json: &str,
+    symbol: &str,
+    params: &Arc<Mutex<SharedMarketMakerParams>>,
+    exchange_client: &Arc<Mutex<ExchangeClient>>,
+    active_orders: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+    log_prefix: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Parse the config
-    match serde_json::from_str::<MarketMakerConfig>(&config_json) {
+    match serde_json::from_str::<MarketMakerConfig>(config_json) {
         Ok(new_config) => {
-            // First check if config has changed - use a separate scope for the lock
-            let config_has_changed = {
-                // Get current shared parameters
-                let shared = params.lock().await;
-                
-                // Check if the config is different
-                shared.config.daily_return_bps != new_config.daily_return_bps || 
-                shared.config.notional_per_side != new_config.notional_per_side || 
-                shared.config.daily_pnl_stop_loss != new_config.daily_pnl_stop_loss ||
-                shared.config.trailing_take_profit != new_config.trailing_take_profit ||
-                shared.config.trailing_stop_loss != new_config.trailing_stop_loss ||
-                shared.config.hedge_only_mode != new_config.hedge_only_mode ||
-                shared.config.force_quote_refresh_interval != new_config.force_quote_refresh_interval ||
-                shared.config.max_long_usd != new_config.max_long_usd ||
-                shared.config.max_short_usd != new_config.max_short_usd ||
-                shared.config.enable_trading != new_config.enable_trading ||
-                quote_levels_changed(&shared.config.quote_levels, &new_config.quote_levels) ||
-                option_string_changed(&shared.config.vault_address, &new_config.vault_address)
-            }; // Lock released here
+            // Take a single read snapshot up front and make every decision
+            // below against it, instead of re-locking (and risking a
+            // concurrent `update_config` interleaving a different version
+            // in) for change detection, the vault-address check, and the
+            // parameter update separately.
+            let snapshot = match read_params_snapshot(params, log_prefix).await {
+                Some(snapshot) => snapshot,
+                None => return Ok(()),
+            };
+
+            // If neither the version nor the content hash moved, this is the
+            // same config we already applied (e.g. the slow-path poll
+            // re-reading a key the keyspace subscriber already handled).
+            // The content hash covers every field on `MarketMakerConfig`, so
+            // unlike a hand-maintained per-field diff it can't silently miss
+            // one added later - that used to happen here: spreads, the
+            // staleness threshold, and the rollover/session-expiry fields
+            // were all dropped until some other, listed field changed too.
+            let version_advanced = new_config.version != snapshot.config.version
+                || config_content_hash(&new_config) != config_content_hash(&snapshot.config);
+            if !version_advanced {
+                debug!("{} No version/content change for {} (version {})", log_prefix, symbol, snapshot.config.version);
+                return Ok(());
+            }
+
+            // Config has changed, log it
+            info!("{} Detected configuration change for {}", log_prefix, symbol);
+            info!("{} New config from Redis: daily_return_bps={}, notional={}, stop_loss={}, tp={}, sl={}, hedge={}, max_long={}, max_short={}, interval={}, enable_trading={}", log_prefix, 
+                new_config.daily_return_bps, 
+                new_config.notional_per_side,
+                new_config.daily_pnl_stop_loss,
+                new_config.trailing_take_profit,
+                new_config.trailing_stop_loss,
+                new_config.hedge_only_mode,
+                new_config.max_long_usd,
+                new_config.max_short_usd,
+                new_config.force_quote_refresh_interval,
+                new_config.enable_trading);
             
-            if config_has_changed {
-                // Config has changed, log it
-                info!("[SLOW PATH] Detected configuration change for {}", symbol);
-                info!("[SLOW PATH] New config from Redis: daily_return_bps={}, notional={}, stop_loss={}, tp={}, sl={}, hedge={}, max_long={}, max_short={}, interval={}, enable_trading={}", 
-                    new_config.daily_return_bps, 
-                    new_config.notional_per_side,
-                    new_config.daily_pnl_stop_loss,
-                    new_config.trailing_take_profit,
-                    new_config.trailing_stop_loss,
-                    new_config.hedge_only_mode,
-                    new_config.max_long_usd,
-                    new_config.max_short_usd,
-                    new_config.force_quote_refresh_interval,
-                    new_config.enable_trading);
+            // Check if vault address changed, against the same snapshot
+            // used for change detection above - not a fresh lock.
+            let vault_address_changed = match (&snapshot.config.vault_address, &new_config.vault_address) {
+                (Some(old), Some(new)) => old != new,
+                (None, Some(_)) => true,
+                (Some(_), None) => true,
+                (None, None) => false,
+            };
+
+            // If vault address changed, we need to recreate the ExchangeClient
+            if vault_address_changed {
+                info!("{} Vault address changed to {:?}. Will recreate ExchangeClient.", log_prefix, 
+                      new_config.vault_address);
                 
-                // Check if vault address changed - use a separate scope to get the shared config
-                let vault_address_changed = {
-                    let shared = params.lock().await;
-                    match (&shared.config.vault_address, &new_config.vault_address) {
-                        (Some(old), Some(new)) => old != new,
-                        (None, Some(_)) => true,
-                        (Some(_), None) => true,
-                        (None, None) => false,
-                    }
+                // Get the wallet from the existing exchange client
+                let wallet = {
+                    let client = exchange_client.lock().await;
+                    client.wallet.clone()
                 };
-
-                // If vault address changed, we need to recreate the ExchangeClient
-                if vault_address_changed {
-                    info!("[SLOW PATH] Vault address changed to {:?}. Will recreate ExchangeClient.", 
-                          new_config.vault_address);
-                    
-                    // Get the wallet from the existing exchange client
-                    let wallet = {
-                        let client = exchange_client.lock().await;
-                        client.wallet.clone()
-                    };
-                    
-                    // Convert vault address String to H160 if present
-                    let vault_address = if let Some(addr_str) = &new_config.vault_address {
-                        use ethers::types::H160;
-                        use std::str::FromStr;
-                        
-                        match H160::from_str(addr_str) {
-                            Ok(h160_addr) => {
-                                info!("[SLOW PATH] Using vault address: {}", addr_str);
-                                Some(h160_addr)
-                            },
-                            Err(e) => {
-                                error!("[SLOW PATH] Invalid vault address format: {}. Error: {}", addr_str, e);
-                                None
-                            }
-                        }
-                    } else {
-                        info!("[SLOW PATH] No vault address specified");
-                        None
-                    };
-                    
-                    // Create new exchange client with updated vault address
-                    let base_url = if cfg!(feature = "testnet") { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
+                
+                // Convert vault address String to H160 if present
+                let vault_address = if let Some(addr_str) = &new_config.vault_address {
+                    use ethers::types::H160;
+                    use std::str::FromStr;
                     
-                    match ExchangeClient::new(
-                        None, 
-                        wallet, 
-                        base_url.clone(), 
-                        None, 
-                        vault_address
-                    ).await {
-                        Ok(new_client) => {
-                            // Replace the exchange client
-                            let mut client_lock = exchange_client.lock().await;
-                            *client_lock = new_client;
-                            info!("[SLOW PATH] Successfully recreated ExchangeClient with new vault address");
+                    match H160::from_str(addr_str) {
+                        Ok(h160_addr) => {
+                            info!("{} Using vault address: {}", log_prefix, addr_str);
+                            Some(h160_addr)
                         },
                         Err(e) => {
-                            error!("[SLOW PATH] Failed to recreate ExchangeClient: {}", e);
+                            error!("{} Invalid vault address format: {}. Error: {}", log_prefix, addr_str, e);
+                            None
                         }
                     }
-                }
-                
-                // Update shared parameters in a separate lock
-                let old_config = {
-                    let mut shared = params.lock().await;
-                    
-                    // Store old config values for logging
-                    let old_config_clone = shared.config.clone();
-                    
-                    // Calculate price precision for the symbol
-                    let price_decimals = match symbol {
-                        "BTC" => 0,
-                        "ETH" => 2,
-                        "SOL" => 3,
-                        "AVAX" => 3,
-                        "MATIC" => 4,
-                        "DOGE" => 6,
-                        "SHIB" => 8,
-                        _ => 2,
-                    };
-                    
-                    // Update the shared parameters
-                    shared.config = new_config.clone();
-                    shared.half_spread = new_config.daily_return_bps / 365;
-                    shared.target_liquidity = new_config.notional_per_side;
-                    shared.max_position_size = if new_config.hedge_only_mode {
-                        0.0
-                    } else {
-                        new_config.max_long_usd.max(new_config.max_short_usd)
-                    };
-                    shared.price_decimals = price_decimals;
-                    shared.needs_refresh = true;
-                    
-                    old_config_clone
-                }; // Lock released here
+                } else {
+                    info!("{} No vault address specified", log_prefix);
+                    None
+                };
                 
-                info!("[SLOW PATH] Updated market maker for {}", symbol);
-                info!("[SLOW PATH] Old config: return_bps={}, notional={}, stop_loss={}, max_long={}, max_short={}, enable_trading={}", 
-                      old_config.daily_return_bps, old_config.notional_per_side, 
-                      old_config.daily_pnl_stop_loss, old_config.max_long_usd,
-                      old_config.max_short_usd, old_config.enable_trading);
-                info!("[SLOW PATH] New config: return_bps={}, notional={}, stop_loss={}, max_long={}, max_short={}, enable_trading={}", 
-                      new_config.daily_return_bps, new_config.notional_per_side, 
-                      new_config.daily_pnl_stop_loss, new_config.max_long_usd,
-                      new_config.max_short_usd, new_config.enable_trading);
+                // Create new exchange client with updated vault address
+                let base_url = if cfg!(feature = "testnet") { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
                 
-                // Cancel orders in a separate step to avoid holding multiple locks
-                // First check if we have any orders to cancel
-                let has_orders = {
+                match ExchangeClient::new(
+                    None, 
+                    wallet, 
+                    base_url.clone(), 
+                    None, 
+                    vault_address
+                ).await {
+                    Ok(new_client) => {
+                        // Replace the exchange client
+                        let mut client_lock = exchange_client.lock().await;
+                        *client_lock = new_client;
+                        info!("{} Successfully recreated ExchangeClient with new vault address", log_prefix);
+                    },
+                    Err(e) => {
+                        error!("{} Failed to recreate ExchangeClient: {}", log_prefix, e);
+                    }
+                }
+            }
+            
+            // Apply the update in exactly one write lock. Price
+            // precision is derived from `shared.asset_meta` inside the
+            // same critical section, so it can't be computed against a
+            // snapshot that's already stale by the time we write.
+            let old_config = match write_params(params, log_prefix, |shared| {
+                let old_config_clone = shared.config.clone();
+                let price_decimals = price_decimals_for_symbol(shared.asset_meta.as_ref(), symbol);
+
+                shared.config = new_config.clone();
+                shared.half_spread = new_config.daily_return_bps / 365;
+                shared.target_liquidity = new_config.notional_per_side;
+                shared.max_position_size = if new_config.hedge_only_mode {
+                    0.0
+                } else {
+                    new_config.max_long_usd.max(new_config.max_short_usd)
+                };
+                shared.price_decimals = price_decimals;
+                shared.needs_refresh = true;
+
+                old_config_clone
+            }).await {
+                Some(old_config) => old_config,
+                None => return Ok(()),
+            };
+
+            info!("{} Updated market maker for {}", log_prefix, symbol);
+            info!("{} Old config: return_bps={}, notional={}, stop_loss={}, max_long={}, max_short={}, enable_trading={}", log_prefix, 
+                  old_config.daily_return_bps, old_config.notional_per_side, 
+                  old_config.daily_pnl_stop_loss, old_config.max_long_usd,
+                  old_config.max_short_usd, old_config.enable_trading);
+            info!("{} New config: return_bps={}, notional={}, stop_loss={}, max_long={}, max_short={}, enable_trading={}", log_prefix, 
+                  new_config.daily_return_bps, new_config.notional_per_side, 
+                  new_config.daily_pnl_stop_loss, new_config.max_long_usd,
+                  new_config.max_short_usd, new_config.enable_trading);
+            
+            // Cancel orders in a separate step to avoid holding multiple locks
+            // First check if we have any orders to cancel
+            let has_orders = {
+                let active_orders_lock = active_orders.lock().await;
+                !active_orders_lock.is_empty()
+            }; // Lock released
+            
+            if has_orders {
+                // Collect oids to cancel, excluding risk-trigger markers which
+                // were never submitted to the exchange
+                let cancels = {
                     let active_orders_lock = active_orders.lock().await;
-                    !active_orders_lock.is_empty()
+                    active_orders_lock.iter()
+                        .filter(|(_, o)| o.trigger_kind.is_none())
+                        .map(|(oid, _)| ClientCancelRequest {
+                            asset: symbol.to_string(),
+                            oid: *oid,
+                        })
+                        .collect::<Vec<_>>()
                 }; // Lock released
-                
-                if has_orders {
-                    // Collect oids to cancel
-                    let cancels = {
-                        let active_orders_lock = active_orders.lock().await;
-                        active_orders_lock.keys()
-                            .map(|oid| ClientCancelRequest {
-                                asset: symbol.to_string(),
-                                oid: *oid,
-                            })
-                            .collect::<Vec<_>>()
-                    }; // Lock released
-                    
-                    // Cancel the orders
-                    if !cancels.is_empty() {
-                        let exchange_client_lock = exchange_client.lock().await;
-                        if let Err(e) = exchange_client_lock.bulk_cancel(cancels, None).await {
-                            warn!("[SLOW PATH] Failed to cancel orders: {}", e);
-                        } else {
-                            info!("[SLOW PATH] Cancelled existing orders to apply new config");
-                            // Clear the order map
-                            let mut active_orders_lock = active_orders.lock().await;
-                            active_orders_lock.clear();
-                        }
+
+                // Cancel the orders
+                if !cancels.is_empty() {
+                    let exchange_client_lock = exchange_client.lock().await;
+                    if let Err(e) = exchange_client_lock.bulk_cancel(cancels, None).await {
+                        warn!("{} Failed to cancel orders: {}", log_prefix, e);
+                    } else {
+                        info!("{} Cancelled existing orders to apply new config", log_prefix);
+                        // Clear the order map, keeping risk-trigger markers intact
+                        let mut active_orders_lock = active_orders.lock().await;
+                        active_orders_lock.retain(|_, o| o.trigger_kind.is_some());
                     }
                 }
-            } else {
-                debug!("[SLOW PATH] No configuration change detected for {}", symbol);
             }
         },
         Err(e) => {
-            error!("[SLOW PATH] Failed to parse configuration from Redis: {}", e);
+            error!("{} Failed to parse configuration from Redis: {}", log_prefix, e);
         }
     }
     
@@ -1264,18 +2237,53 @@ async fn slow_path_check_config(
 }
 
 /// Helper function to store configuration in Redis
-async fn store_config_in_redis(redis_client: &RedisClient, config: &MarketMakerConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Convert config to JSON
-    let config_json = serde_json::to_string(config)?;
-    
-    // Connect to Redis
-    let mut conn = redis_client.get_async_connection().await?;
-    
-    // Store configuration with symbol as key
+/// Compare-and-set script for `store_config_in_redis`: commits the new
+/// config only if the version currently stored for the key still matches
+/// `ARGV[1]` (the version the writer last read), so two writers racing on
+/// the same symbol can't silently clobber each other. Returns the
+/// conflicting version found in Redis, or -1 on a successful commit.
+const CAS_STORE_CONFIG_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+local current_version = 0
+if current then
+    local decoded = cjson.decode(current)
+    current_version = decoded.version or 0
+end
+if current_version ~= tonumber(ARGV[1]) then
+    return current_version
+end
+redis.call('SET', KEYS[1], ARGV[2])
+return -1
+"#;
+
+/// Store `config` in Redis under a compare-and-set guard: the write only
+/// commits if the version stored for `config.symbol` still matches
+/// `config.version` (what this writer last saw), and bumps the stored
+/// version by one on success. Returns an error describing the conflict
+/// instead of overwriting a version this writer never saw.
+async fn store_config_in_redis(redis_pool: &RedisPool, config: &MarketMakerConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let key = format!("config:{}", config.symbol);
-    conn.set::<_, _, ()>(key, &config_json).await?;
-    
-    debug!("Stored configuration in Redis for {}: {}", config.symbol, config_json);
-    
+    let mut conn = redis_pool.get().await?;
+
+    let mut next_config = config.clone();
+    next_config.version = config.version.wrapping_add(1);
+    let config_json = serde_json::to_string(&next_config)?;
+
+    let observed_version: i64 = redis::Script::new(CAS_STORE_CONFIG_SCRIPT)
+        .key(&key)
+        .arg(config.version)
+        .arg(&config_json)
+        .invoke_async(&mut conn)
+        .await?;
+
+    if observed_version >= 0 {
+        return Err(format!(
+            "config write conflict for {}: expected version {}, Redis has {}",
+            config.symbol, config.version, observed_version
+        ).into());
+    }
+
+    debug!("Stored configuration in Redis for {} at version {}: {}", config.symbol, next_config.version, config_json);
+
     Ok(())
 } 
\ No newline at end of file
@@ -0,0 +1,62 @@
+use thiserror::Error;
+
+/// Errors that can occur while the symbol scanner talks to Redis and the
+/// Hyperliquid API. Distinguishes failures worth retrying (a dropped
+/// connection, a rate limit) from ones that won't resolve by themselves.
+#[derive(Debug, Error)]
+pub enum ScannerError {
+    #[error("redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("redis pool error: {0}")]
+    RedisPool(#[from] bb8::RunError<redis::RedisError>),
+
+    #[error("hyperliquid sdk error: {0}")]
+    Sdk(#[from] hyperliquid_rust_sdk::Error),
+
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("configuration error: {0}")]
+    Config(String),
+
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+}
+
+impl ScannerError {
+    /// Whether the operation that produced this error is worth retrying.
+    /// Redis/SDK hiccups and rate limiting are transient; bad configuration
+    /// and unparseable payloads will fail again on retry.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ScannerError::Redis(_) => true,
+            ScannerError::RedisPool(_) => true,
+            ScannerError::Sdk(_) => true,
+            ScannerError::RateLimited(_) => true,
+            ScannerError::Serde(_) => false,
+            ScannerError::Config(_) => false,
+        }
+    }
+}
+
+/// Errors from validating a `MarketMakerConfig` update before it's applied.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("symbol cannot be empty")]
+    EmptySymbol,
+    #[error("daily return bps must be greater than 0")]
+    InvalidDailyReturnBps,
+    #[error("notional per side must be greater than 0")]
+    InvalidNotionalPerSide,
+    #[error("daily PNL stop loss must be greater than 0")]
+    InvalidDailyPnlStopLoss,
+    #[error("trailing take profit must be between 0 and 1")]
+    InvalidTrailingTakeProfit,
+    #[error("trailing stop loss must be between 0 and 1")]
+    InvalidTrailingStopLoss,
+    #[error("force quote refresh interval must be at least 100ms")]
+    RefreshIntervalTooShort,
+    #[error("position limits cannot be negative")]
+    NegativePositionLimit,
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use hyperliquid_rust_sdk::{
+    ClientCancelRequest, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient,
+    ExchangeDataStatus, ExchangeResponseStatus, MarketOrderParams,
+};
+
+use crate::backtest::SimulatedExchange;
+use crate::reconciliation::DesiredOrder;
+
+/// Outcome of placing one order through `ExchangeBackend::bulk_order`,
+/// normalized away from whichever wire format the concrete backend speaks.
+#[derive(Clone, Debug)]
+pub enum BackendOrderStatus {
+    Resting { oid: u64 },
+    Error(String),
+}
+
+/// Response to a `bulk_order` call, one status per order in the same order
+/// they were submitted in.
+#[derive(Clone, Debug, Default)]
+pub struct BackendOrderResponse {
+    pub statuses: Vec<BackendOrderStatus>,
+}
+
+/// The exchange operations the maker trades through:
+/// `reconciliation::execute_plan` uses `bulk_order` and `bulk_cancel`, and
+/// `market_open` is the market-order path. Both `ExchangeClient` and `SimulatedExchange` implement it, so the
+/// reconcile staging logic type-checks against either - but `EnhancedMarketMaker`
+/// still only ever constructs it with the live `ExchangeClient`; wiring the
+/// live quoting loop to run against `SimulatedExchange` for paper trading
+/// (rather than `SimulatedExchange` replaying its own book in
+/// `backtest::run_replay`, as it does today) is still open, partly because
+/// `EnhancedMarketMaker`'s Redis-driven config reload can recreate the
+/// backend on a vault address change (see `apply_config_from_redis`), which
+/// has no equivalent for a backend that isn't a real account.
+#[async_trait]
+pub trait ExchangeBackend: Send + Sync {
+    /// Place one or more limit orders for `symbol`, returning a status per
+    /// order in submission order.
+    async fn bulk_order(&self, symbol: &str, orders: Vec<DesiredOrder>) -> Result<BackendOrderResponse, String>;
+
+    /// Cancel the given order ids for `symbol`.
+    async fn bulk_cancel(&self, symbol: &str, oids: Vec<u64>) -> Result<(), String>;
+
+    /// Flatten/open a position at market for `symbol`, modeling up to
+    /// `slippage` (a fraction of price, e.g. 0.03 for 3%) against the trader.
+    async fn market_open(&self, symbol: &str, is_buy: bool, size: f64, slippage: f64) -> Result<(), String>;
+}
+
+#[async_trait]
+impl ExchangeBackend for ExchangeClient {
+    async fn bulk_order(&self, symbol: &str, orders: Vec<DesiredOrder>) -> Result<BackendOrderResponse, String> {
+        let requests: Vec<ClientOrderRequest> = orders.iter().map(|d| ClientOrderRequest {
+            asset: symbol.to_string(),
+            is_buy: d.is_bid,
+            reduce_only: false,
+            limit_px: d.price,
+            sz: d.size,
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit { tif: "Alo".to_string() }),
+        }).collect();
+
+        match ExchangeClient::bulk_order(self, requests, None).await {
+            Ok(ExchangeResponseStatus::Ok(ok_response)) => {
+                let statuses = ok_response.data
+                    .map(|data| data.statuses.into_iter().map(|status| match status {
+                        ExchangeDataStatus::Resting(order) => BackendOrderStatus::Resting { oid: order.oid },
+                        other => BackendOrderStatus::Error(format!("{:?}", other)),
+                    }).collect())
+                    .unwrap_or_default();
+                Ok(BackendOrderResponse { statuses })
+            },
+            Ok(ExchangeResponseStatus::Err(e)) => Err(e),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn bulk_cancel(&self, symbol: &str, oids: Vec<u64>) -> Result<(), String> {
+        let requests: Vec<ClientCancelRequest> = oids.into_iter()
+            .map(|oid| ClientCancelRequest { asset: symbol.to_string(), oid })
+            .collect();
+
+        match ExchangeClient::bulk_cancel(self, requests, None).await {
+            Ok(ExchangeResponseStatus::Ok(_)) => Ok(()),
+            Ok(ExchangeResponseStatus::Err(e)) => Err(e),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn market_open(&self, symbol: &str, is_buy: bool, size: f64, slippage: f64) -> Result<(), String> {
+        let params = MarketOrderParams {
+            asset: symbol,
+            is_buy,
+            sz: size,
+            px: None,
+            slippage: Some(slippage),
+            cloid: None,
+            wallet: None,
+        };
+
+        match ExchangeClient::market_open(self, params).await {
+            Ok(_response) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// `SimulatedExchange` is driven through `&mut self` (it's a local book, not
+/// a network client), so it implements the backend through the same
+/// `Arc<Mutex<...>>` wrapper the rest of this crate already uses to share
+/// mutable state across the websocket loop.
+#[async_trait]
+impl ExchangeBackend for Arc<Mutex<SimulatedExchange>> {
+    async fn bulk_order(&self, symbol: &str, orders: Vec<DesiredOrder>) -> Result<BackendOrderResponse, String> {
+        let mut sim = self.lock().await;
+        if sim.symbol() != symbol {
+            return Err(format!("SimulatedExchange is configured for {}, not {}", sim.symbol(), symbol));
+        }
+
+        let statuses = orders.into_iter()
+            .map(|order| match sim.place_order(order.is_bid, order.level, order.price, order.size) {
+                Ok(oid) => BackendOrderStatus::Resting { oid },
+                Err(e) => BackendOrderStatus::Error(e),
+            })
+            .collect();
+
+        Ok(BackendOrderResponse { statuses })
+    }
+
+    async fn bulk_cancel(&self, _symbol: &str, oids: Vec<u64>) -> Result<(), String> {
+        let mut sim = self.lock().await;
+        for oid in oids {
+            sim.cancel_order(oid);
+        }
+        Ok(())
+    }
+
+    async fn market_open(&self, symbol: &str, is_buy: bool, size: f64, slippage: f64) -> Result<(), String> {
+        let mut sim = self.lock().await;
+        if sim.symbol() != symbol {
+            return Err(format!("SimulatedExchange is configured for {}, not {}", sim.symbol(), symbol));
+        }
+        sim.simulate_market_order(is_buy, size, slippage);
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+
+use crate::position_manager::PositionSummary;
+use crate::redis_pool::RedisPool;
+
+/// A destination for published position summaries. `PositionManager` fans
+/// each summary out to every configured sink, so adding durable storage (or
+/// any other downstream) is a matter of implementing this trait rather than
+/// hard-wiring another write into the publish path.
+#[async_trait]
+pub trait HistorySink: Send + Sync {
+    async fn record(&self, summary: &PositionSummary) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Appends each summary to the `position_history` Redis stream. This is the
+/// original hard-wired behavior, now just one sink among possibly several.
+pub struct RedisStreamSink {
+    redis_pool: RedisPool,
+}
+
+impl RedisStreamSink {
+    pub fn new(redis_pool: RedisPool) -> Self {
+        RedisStreamSink { redis_pool }
+    }
+}
+
+#[async_trait]
+impl HistorySink for RedisStreamSink {
+    async fn record(&self, summary: &PositionSummary) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.redis_pool.get().await?;
+        let summary_json = serde_json::to_string(summary)?;
+        let stream_data = vec![("data", summary_json)];
+        conn.xadd("position_history", "*", &stream_data).await?;
+        Ok(())
+    }
+}
+
+/// Inserts each summary, and a flattened row per position, into Postgres so
+/// operators can run SQL analytics/backtests over historical PnL and
+/// exposure instead of scraping the Redis stream.
+///
+/// Expects a schema along these lines:
+///
+/// ```sql
+/// CREATE TABLE position_summaries (
+///     seq                  BIGINT PRIMARY KEY,
+///     ts                   BIGINT NOT NULL,
+///     total_pnl            DOUBLE PRECISION NOT NULL,
+///     total_realized_pnl   DOUBLE PRECISION NOT NULL,
+///     total_long_exposure  DOUBLE PRECISION NOT NULL,
+///     total_short_exposure DOUBLE PRECISION NOT NULL
+/// );
+///
+/// CREATE TABLE position_rows (
+///     seq             BIGINT NOT NULL REFERENCES position_summaries(seq),
+///     symbol          TEXT NOT NULL,
+///     size            DOUBLE PRECISION NOT NULL,
+///     entry_price     DOUBLE PRECISION NOT NULL,
+///     current_price   DOUBLE PRECISION NOT NULL,
+///     unrealized_pnl  DOUBLE PRECISION NOT NULL,
+///     realized_pnl    DOUBLE PRECISION NOT NULL,
+///     notional_usd    DOUBLE PRECISION NOT NULL,
+///     version         BIGINT NOT NULL,
+///     last_update_ts  BIGINT NOT NULL
+/// );
+/// ```
+pub struct PostgresSink {
+    pool: bb8::Pool<bb8_postgres::PostgresConnectionManager<tokio_postgres::NoTls>>,
+}
+
+impl PostgresSink {
+    /// Connect using a standard Postgres connection string, e.g.
+    /// `host=localhost user=mm dbname=mm_history`.
+    pub async fn new(connection_string: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let manager = bb8_postgres::PostgresConnectionManager::new_from_stringlike(
+            connection_string,
+            tokio_postgres::NoTls,
+        )?;
+        let pool = bb8::Pool::builder().max_size(8).build(manager).await?;
+
+        Ok(PostgresSink { pool })
+    }
+}
+
+#[async_trait]
+impl HistorySink for PostgresSink {
+    async fn record(&self, summary: &PositionSummary) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "INSERT INTO position_summaries (seq, ts, total_pnl, total_realized_pnl, total_long_exposure, total_short_exposure) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                &(summary.seq as i64),
+                &(summary.timestamp as i64),
+                &summary.total_pnl,
+                &summary.total_realized_pnl,
+                &summary.total_long_exposure,
+                &summary.total_short_exposure,
+            ],
+        ).await?;
+
+        for position in &summary.positions {
+            tx.execute(
+                "INSERT INTO position_rows \
+                 (seq, symbol, size, entry_price, current_price, unrealized_pnl, realized_pnl, notional_usd, version, last_update_ts) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                &[
+                    &(summary.seq as i64),
+                    &position.symbol,
+                    &position.size,
+                    &position.entry_price,
+                    &position.current_price,
+                    &position.unrealized_pnl,
+                    &position.realized_pnl,
+                    &position.notional_usd,
+                    &(position.version as i64),
+                    &(position.last_update_ts as i64),
+                ],
+            ).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,40 @@
 mod enhanced_market_maker;
+mod candle_aggregator;
 mod symbol_scanner;
 mod config_service;
 mod position_manager;
+mod redis_pool;
+mod candle_backfill;
+mod rate_limiter;
+mod metrics;
+mod resilient_pubsub;
+mod errors;
+mod history_sink;
+mod backtest;
+mod price_source;
+mod reconciliation;
+mod exchange_backend;
+mod position_events;
+mod lifecycle_manager;
+mod control_server;
 mod util;
 
-pub use enhanced_market_maker::{EnhancedMarketMaker, MarketMakerConfig, Position};
+pub use enhanced_market_maker::{EnhancedMarketMaker, MarketMakerConfig, Position, TriggerKind, SessionExpirySchedule};
+pub use candle_aggregator::{CandleAggregator, CandleSource, LiveCandle};
 pub use symbol_scanner::{SymbolScanner, SymbolMetrics};
-pub use config_service::ConfigService;
-pub use position_manager::{PositionManager, PositionSummary}; 
+pub use config_service::{ConfigService, ConfigMessage};
+pub use position_manager::{PositionManager, PositionSummary, FillSide, InstrumentMetadata};
+pub use redis_pool::{RedisPool, RedisConnectionManager, create_redis_pool, DEFAULT_POOL_MAX_SIZE, DEFAULT_POOL_CONNECTION_TIMEOUT};
+pub use candle_backfill::Resolution;
+pub use metrics::run_metrics_server;
+pub use resilient_pubsub::ResilientPubSub;
+pub use errors::{ScannerError, ConfigError};
+pub use history_sink::{HistorySink, RedisStreamSink, PostgresSink};
+pub use backtest::{SimulatedExchange, ReplayTick, BacktestSummary, MAX_NUM_LIMIT_ORDERS};
+pub use price_source::{RatePriceSource, ExternalTickerSource};
+pub use reconciliation::{DesiredOrder, ReconciliationPlan, ReplacementOrder, diff_orders, execute_plan};
+pub use exchange_backend::{ExchangeBackend, BackendOrderResponse, BackendOrderStatus};
+pub use position_events::{PositionUpdateEvent, PositionDelta, PositionSnapshot, PositionUpdateSender, PositionUpdateReceiver};
+pub use lifecycle_manager::{LifecycleManager, LifecycleState, SymbolStatus};
+pub use control_server::{ControlServerState, run_control_server};
 pub use util::helper_structs::Mode;
\ No newline at end of file
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ethers::signers::LocalWallet;
+use log::{error, info, warn};
+use redis::Client as RedisClient;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::enhanced_market_maker::config_content_hash;
+use crate::redis_pool::RedisPool;
+use crate::{EnhancedMarketMaker, MarketMakerConfig, Position};
+
+/// Backoff before the first respawn attempt after `EnhancedMarketMaker::new`
+/// fails for a symbol.
+const INITIAL_REPAIR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Cap on the exponential backoff between respawn attempts, so a
+/// persistently broken symbol is retried every 2 minutes rather than
+/// hammering the exchange/Redis.
+const MAX_REPAIR_BACKOFF: Duration = Duration::from_secs(120);
+
+/// How long `stop_symbol` waits for a single rolled instance to drain its
+/// resting orders and exit before aborting its task outright.
+const SYMBOL_STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Where a symbol's market maker instance is in its lifecycle, driven by
+/// [`LifecycleManager::reconcile`] once per tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleState {
+    /// `EnhancedMarketMaker::new` is being (re-)run for this symbol.
+    Initializing,
+    /// The quoting task is spawned and its config matches the desired config.
+    Running,
+    /// The live instance was built from a config that has since changed;
+    /// it's about to be stopped and replaced.
+    Outdated,
+    /// The quoting task ended without anyone asking it to.
+    Unhealthy,
+    /// `EnhancedMarketMaker::new` failed; waiting out an exponential backoff
+    /// before trying again.
+    Repairing,
+    /// The instance's task has been asked to stop and is being torn down.
+    Stopping,
+    /// The instance has been torn down and no task is running for it.
+    Stopped,
+}
+
+/// Everything the manager tracks about one symbol's market maker instance.
+struct SymbolLifecycle {
+    state: LifecycleState,
+    /// Content hash of the config the live instance was built from, used to
+    /// detect `Outdated` without storing the whole config twice.
+    config_hash: u64,
+    handle: Option<JoinHandle<()>>,
+    /// Clone of the live instance's `trading_enabled` flag. The instance
+    /// itself is moved into its quoting task outright (not shared), so this
+    /// flag is the only way `set_enable_trading` can reach it from outside.
+    trading_enabled: Option<Arc<AtomicBool>>,
+    /// Child of the manager's shutdown token: cancelling it stops only this
+    /// symbol's task (for an `Outdated` roll); cancelling the parent stops
+    /// every symbol at once (for a full engine shutdown).
+    cancel: CancellationToken,
+    repair_attempts: u32,
+    next_repair_at: Instant,
+    /// Set when this entry last transitioned into `Running`, so a status
+    /// endpoint can report uptime; `None` until the first successful start.
+    started_at: Option<Instant>,
+}
+
+/// Point-in-time status for one symbol, exposed read-only to the control
+/// server. Unlike `LifecycleState` alone, this carries what `ListMarketMakers`/
+/// `GetMarketMaker` need to report: the config the live instance was built
+/// from, how long it's been running, and its current position.
+#[derive(Clone, Debug, Serialize)]
+pub struct SymbolStatus {
+    pub state: LifecycleState,
+    pub config_hash: u64,
+    pub repair_attempts: u32,
+    /// Seconds since this instance last transitioned to `Running`; `None`
+    /// if it has never successfully started.
+    pub uptime_secs: Option<u64>,
+    pub position: Option<Position>,
+}
+
+/// Drives each symbol's `EnhancedMarketMaker` through an explicit
+/// [`LifecycleState`] state machine instead of the ad-hoc new/update
+/// branching `run_market_making_engine` used to do inline.
+///
+/// `reconcile` is meant to be called once per poll tick with the desired
+/// configs; it spawns missing instances, restarts ones whose task died,
+/// backs off retrying ones that failed to initialize, and rolls instances
+/// whose config changed. Per-symbol state is exposed via `state_snapshot`
+/// and `status_snapshot` so operators can see why a symbol isn't trading,
+/// and `set_enable_trading` lets a control server pause/resume one without
+/// a config round-trip through Redis.
+pub struct LifecycleManager {
+    wallet: LocalWallet,
+    redis_pool: RedisPool,
+    redis_client: RedisClient,
+    positions: Arc<RwLock<HashMap<String, Position>>>,
+    /// Parent of every symbol's `cancel` token; cancelling it (via
+    /// `shutdown`) stops every symbol at once.
+    shutdown: CancellationToken,
+    symbols: HashMap<String, SymbolLifecycle>,
+}
+
+impl LifecycleManager {
+    pub fn new(
+        wallet: LocalWallet,
+        redis_pool: RedisPool,
+        redis_client: RedisClient,
+        positions: Arc<RwLock<HashMap<String, Position>>>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        LifecycleManager {
+            wallet,
+            redis_pool,
+            redis_client,
+            positions,
+            shutdown,
+            symbols: HashMap::new(),
+        }
+    }
+
+    /// Snapshot of every tracked symbol's current lifecycle state, for
+    /// operator visibility (e.g. a status endpoint).
+    pub fn state_snapshot(&self) -> HashMap<String, LifecycleState> {
+        self.symbols.iter().map(|(symbol, entry)| (symbol.clone(), entry.state)).collect()
+    }
+
+    /// Richer per-symbol status for the control server's `ListMarketMakers`/
+    /// `GetMarketMaker` calls: state plus config hash, repair count, uptime,
+    /// and current position.
+    pub async fn status_snapshot(&self) -> HashMap<String, SymbolStatus> {
+        let positions = self.positions.read().await;
+
+        self.symbols
+            .iter()
+            .map(|(symbol, entry)| {
+                let status = SymbolStatus {
+                    state: entry.state,
+                    config_hash: entry.config_hash,
+                    repair_attempts: entry.repair_attempts,
+                    uptime_secs: entry.started_at.map(|started| started.elapsed().as_secs()),
+                    position: positions.get(symbol).cloned(),
+                };
+                (symbol.clone(), status)
+            })
+            .collect()
+    }
+
+    /// Flip `enable_trading` on the live instance for `symbol` without
+    /// touching Redis, for the control server's `PauseSymbol`/`ResumeSymbol`
+    /// RPCs. Stores directly to the tracked `trading_enabled` flag instead
+    /// of reaching into the instance, since it's owned outright by its
+    /// quoting task for as long as that task runs. Returns `false` if no
+    /// live instance is tracked for `symbol` (e.g. it's `Repairing` or
+    /// `Stopped`).
+    pub async fn set_enable_trading(&self, symbol: &str, enabled: bool) -> bool {
+        match self.symbols.get(symbol).and_then(|entry| entry.trading_enabled.as_ref()) {
+            Some(flag) => {
+                flag.store(enabled, Ordering::Relaxed);
+                info!("Lifecycle: {} trading for {} via control server", if enabled { "resumed" } else { "paused" }, symbol);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reconcile desired `configs` against the observed state of each
+    /// tracked instance: spawn anything missing, restart anything whose
+    /// task ended unexpectedly, retry anything still backing off from an
+    /// init failure, roll anything whose config changed, and tear down
+    /// anything tracked whose config has disappeared from `configs`. Meant
+    /// as a low-frequency fallback scan; `reconcile_one` reacts to a single
+    /// symbol's update immediately instead of waiting for the next scan.
+    pub async fn reconcile(&mut self, configs: &HashMap<String, MarketMakerConfig>) {
+        for (symbol, config) in configs.iter() {
+            self.reconcile_one(symbol, config).await;
+        }
+
+        let stale: Vec<String> = self
+            .symbols
+            .keys()
+            .filter(|symbol| !configs.contains_key(*symbol))
+            .cloned()
+            .collect();
+
+        for symbol in stale {
+            info!("Lifecycle: {} no longer has a config, tearing down its instance", symbol);
+            self.remove_symbol(&symbol).await;
+        }
+    }
+
+    /// Reconcile a single symbol's desired `config` against its observed
+    /// state. Called per-entry by `reconcile`'s full scan, and directly by
+    /// the engine's Redis pub/sub listener so an individual config update
+    /// is applied the moment it's published rather than on the next scan.
+    pub async fn reconcile_one(&mut self, symbol: &str, config: &MarketMakerConfig) {
+        let state = self.symbols.get(symbol).map(|entry| entry.state);
+
+        match state {
+            None => {
+                info!("Lifecycle: no instance tracked for {}, starting one", symbol);
+                self.start_symbol(symbol, config).await;
+            }
+            Some(LifecycleState::Repairing) => {
+                let due = self.symbols.get(symbol).map(|entry| entry.next_repair_at);
+                if due.map(|due| Instant::now() >= due).unwrap_or(false) {
+                    info!("Lifecycle: retrying repair for {}", symbol);
+                    self.start_symbol(symbol, config).await;
+                }
+            }
+            Some(LifecycleState::Running) | Some(LifecycleState::Outdated) => {
+                let finished = self
+                    .symbols
+                    .get(symbol)
+                    .and_then(|entry| entry.handle.as_ref())
+                    .map(|handle| handle.is_finished())
+                    .unwrap_or(false);
+
+                if finished {
+                    warn!("Lifecycle: {} task ended unexpectedly, marking Unhealthy and restarting", symbol);
+                    if let Some(entry) = self.symbols.get_mut(symbol) {
+                        entry.state = LifecycleState::Unhealthy;
+                    }
+                    self.start_symbol(symbol, config).await;
+                    return;
+                }
+
+                let live_hash = self.symbols.get(symbol).map(|entry| entry.config_hash);
+                if live_hash != Some(config_content_hash(config)) {
+                    info!("Lifecycle: config changed for {}, marking Outdated and rolling the instance", symbol);
+                    if let Some(entry) = self.symbols.get_mut(symbol) {
+                        entry.state = LifecycleState::Outdated;
+                    }
+                    self.stop_symbol(symbol).await;
+                    self.start_symbol(symbol, config).await;
+                }
+            }
+            Some(LifecycleState::Initializing)
+            | Some(LifecycleState::Unhealthy)
+            | Some(LifecycleState::Stopping)
+            | Some(LifecycleState::Stopped) => {
+                // Mid-transition states `start_symbol`/`stop_symbol` leave
+                // behind only momentarily; nothing to reconcile here.
+            }
+        }
+    }
+
+    /// Build a fresh `EnhancedMarketMaker` for `symbol` and spawn its
+    /// quoting task, recording the outcome as `Running` or, on failure,
+    /// `Repairing` with an exponential backoff before the next attempt.
+    async fn start_symbol(&mut self, symbol: &str, config: &MarketMakerConfig) {
+        let repair_attempts = self.symbols.get(symbol).map(|entry| entry.repair_attempts).unwrap_or(0);
+        let cancel = self.shutdown.child_token();
+
+        self.symbols.insert(
+            symbol.to_string(),
+            SymbolLifecycle {
+                state: LifecycleState::Initializing,
+                config_hash: config_content_hash(config),
+                handle: None,
+                trading_enabled: None,
+                cancel: cancel.clone(),
+                repair_attempts,
+                next_repair_at: Instant::now(),
+                started_at: None,
+            },
+        );
+
+        match EnhancedMarketMaker::new(config.clone(), self.wallet.clone(), self.redis_pool.clone(), self.redis_client.clone()).await {
+            Ok(mut maker) => {
+                info!("Lifecycle: {} initialized, starting quoting task", symbol);
+                let trading_enabled = maker.trading_enabled_handle();
+                let symbol_owned = symbol.to_string();
+
+                let handle = tokio::spawn(async move {
+                    if let Err(e) = maker.start(cancel).await {
+                        error!("Market maker task for {} exited with error: {}", symbol_owned, e);
+                    }
+                });
+
+                if let Some(entry) = self.symbols.get_mut(symbol) {
+                    entry.state = LifecycleState::Running;
+                    entry.trading_enabled = Some(trading_enabled);
+                    entry.handle = Some(handle);
+                    entry.repair_attempts = 0;
+                    entry.started_at = Some(Instant::now());
+                }
+            }
+            Err(e) => {
+                error!("Lifecycle: failed to initialize market maker for {}: {}", symbol, e);
+                if let Some(entry) = self.symbols.get_mut(symbol) {
+                    entry.repair_attempts += 1;
+                    let backoff = Self::repair_backoff(entry.repair_attempts);
+                    entry.state = LifecycleState::Repairing;
+                    entry.next_repair_at = Instant::now() + backoff;
+                    warn!(
+                        "Lifecycle: {} entering Repairing state, retrying in {:?} (attempt {})",
+                        symbol, backoff, entry.repair_attempts
+                    );
+                }
+            }
+        }
+    }
+
+    /// Exponential backoff for the `attempt`-th repair retry (1-indexed),
+    /// doubling from `INITIAL_REPAIR_BACKOFF` and capped at `MAX_REPAIR_BACKOFF`.
+    fn repair_backoff(attempt: u32) -> Duration {
+        let capped_attempt = attempt.min(8);
+        let millis = INITIAL_REPAIR_BACKOFF.as_millis() as u64 * 2u64.saturating_pow(capped_attempt.saturating_sub(1));
+        Duration::from_millis(millis).min(MAX_REPAIR_BACKOFF)
+    }
+
+    /// Stop the tracked instance for `symbol`, if any: cancel its token so
+    /// `EnhancedMarketMaker::start` can cancel resting orders and persist
+    /// final state on its way out, then wait up to `SYMBOL_STOP_GRACE`
+    /// before aborting its task outright.
+    async fn stop_symbol(&mut self, symbol: &str) {
+        if let Some(entry) = self.symbols.get_mut(symbol) {
+            entry.state = LifecycleState::Stopping;
+            entry.cancel.cancel();
+
+            if let Some(mut handle) = entry.handle.take() {
+                let sleep = tokio::time::sleep(SYMBOL_STOP_GRACE);
+                tokio::pin!(sleep);
+                tokio::select! {
+                    result = &mut handle => {
+                        if let Err(e) = result {
+                            warn!("Lifecycle: {} quoting task panicked while stopping: {}", symbol, e);
+                        }
+                    }
+                    _ = &mut sleep => {
+                        warn!("Lifecycle: {} did not stop within {:?}, aborting its task", symbol, SYMBOL_STOP_GRACE);
+                        handle.abort();
+                    }
+                }
+            }
+
+            entry.trading_enabled = None;
+            entry.state = LifecycleState::Stopped;
+        }
+    }
+
+    /// Tear down the tracked instance for `symbol` (cancel, await drain,
+    /// drop its handle) and remove it from `symbols` entirely, as opposed
+    /// to `stop_symbol` which leaves a `Stopped` entry behind for a roll.
+    /// Called when a symbol's config disappears from the desired set or an
+    /// explicit delete is published, since there's no longer anything to
+    /// reconcile it against.
+    pub async fn remove_symbol(&mut self, symbol: &str) {
+        self.stop_symbol(symbol).await;
+        self.symbols.remove(symbol);
+    }
+
+    /// Stop every tracked symbol for a full engine shutdown: cancel every
+    /// symbol's token up front so they drain concurrently, then wait up to
+    /// `grace` in total across all of them before aborting any stragglers.
+    pub async fn shutdown(&mut self, grace: Duration) {
+        for entry in self.symbols.values() {
+            entry.cancel.cancel();
+        }
+
+        let deadline = Instant::now() + grace;
+        let symbols: Vec<String> = self.symbols.keys().cloned().collect();
+
+        for symbol in symbols {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if let Some(entry) = self.symbols.get_mut(&symbol) {
+                entry.state = LifecycleState::Stopping;
+
+                if let Some(mut handle) = entry.handle.take() {
+                    match tokio::time::timeout(remaining, &mut handle).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => warn!("Lifecycle: {} quoting task panicked while stopping: {}", symbol, e),
+                        Err(_) => {
+                            warn!("Lifecycle: {} did not stop within the shutdown grace period, aborting its task", symbol);
+                            handle.abort();
+                        }
+                    }
+                }
+
+                entry.trading_enabled = None;
+                entry.state = LifecycleState::Stopped;
+            }
+        }
+    }
+}
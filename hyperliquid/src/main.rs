@@ -3,42 +3,81 @@ use std::env;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use log::{info, error, warn};
+use tokio_util::sync::CancellationToken;
+use log::{info, error};
 use redis::Client as RedisClient;
 use ethers::signers::{LocalWallet, Signer};
 
 // Import our custom modules
 use hyperliquid_market_maker::{
-    EnhancedMarketMaker,
     MarketMakerConfig,
     Position,
     SymbolScanner,
     ConfigService,
+    ConfigMessage,
     PositionManager,
-    Mode
+    Mode,
+    create_redis_pool,
+    run_metrics_server,
+    HistorySink,
+    RedisStreamSink,
+    PostgresSink,
+    RedisPool,
+    LifecycleManager,
+    ResilientPubSub,
+    DEFAULT_POOL_MAX_SIZE,
+    DEFAULT_POOL_CONNECTION_TIMEOUT,
+    ControlServerState,
+    run_control_server,
 };
 
+/// How long a graceful shutdown waits for in-flight order cancellation and
+/// state persistence to finish before the process exits anyway.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How often `run_market_making_engine` falls back to a full scan of
+/// `configs` in case a `mm_config` pub/sub message was missed (e.g. a
+/// reconnect gap). Individual updates are applied immediately as they're
+/// published; this is just a low-frequency safety net.
+const RECONCILE_FALLBACK_INTERVAL: Duration = Duration::from_secs(30);
+
 // This will be our main application
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
-    // Initialize Redis connection first as it's needed by all modes
-    let redis_client = match init_redis().await {
-        Ok(client) => client,
+    // Cancelled by the signal handler below; threaded into every long-running
+    // task so SIGINT/SIGTERM trigger an orderly stop (cancel resting orders,
+    // persist final state) instead of the process just being killed mid-flight.
+    let shutdown = CancellationToken::new();
+    spawn_shutdown_signal_listener(shutdown.clone());
+
+    // Initialize Redis connection first as it's needed by all modes. The
+    // pool is built once here and shared by every service below, instead of
+    // each independently opening its own pool on top of the same client.
+    let (redis_client, redis_pool) = match init_redis().await {
+        Ok(handles) => handles,
         Err(e) => {
             error!("Failed to initialize Redis: {}", e);
             return;
         }
     };
 
+    // Expose scanner/config health as Prometheus metrics for every mode
+    let metrics_addr = env::var("METRICS_ADDR").unwrap_or_else(|_| "0.0.0.0:9100".to_string());
+    tokio::spawn(async move {
+        if let Err(e) = run_metrics_server(&metrics_addr).await {
+            error!("Metrics server error: {}", e);
+        }
+    });
+
     // Run the appropriate service based on the mode
     match env::var("MODE") {
         Ok(mode) => {
             match mode.as_str() {
                 "MarketMaker" => {
                     info!("Starting Hyperliquid Market Maker");
-                    
+
                     // Get wallet private key from environment variable (only needed for MarketMaker)
                     let wallet = match init_wallet() {
                         Ok(wallet) => wallet,
@@ -47,27 +86,27 @@ async fn main() {
                             return;
                         }
                     };
-                    
+
                     // Run market maker
-                    if let Err(e) = run_market_maker_service(redis_client, wallet).await {
+                    if let Err(e) = run_market_maker_service(redis_client, redis_pool, wallet, shutdown).await {
                         error!("Market maker service error: {}", e);
                     }
                 }
                 "SymbolScanner" => {
                     info!("Starting Symbol Scanner");
-                    if let Err(e) = run_symbol_scanner_service(redis_client).await {
+                    if let Err(e) = run_symbol_scanner_service(redis_pool, shutdown).await {
                         error!("Symbol scanner service error: {}", e);
                     }
                 }
                 "ConfigService" => {
                     info!("Starting Config Service");
-                    if let Err(e) = run_config_service(redis_client).await {
+                    if let Err(e) = run_config_service(redis_client, redis_pool, shutdown).await {
                         error!("Config service error: {}", e);
                     }
                 }
                 "PositionManager" => {
                     info!("Starting Position Manager");
-                    if let Err(e) = run_position_manager_service(redis_client).await {
+                    if let Err(e) = run_position_manager_service(redis_pool, shutdown).await {
                         error!("Position manager service error: {}", e);
                     }
                 }
@@ -82,6 +121,52 @@ async fn main() {
     }
 }
 
+// Listen for SIGINT (ctrl-c) and, on unix, SIGTERM, and cancel `shutdown` the
+// first time either arrives so every task selecting on it unwinds.
+fn spawn_shutdown_signal_listener(shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        {
+            let mut sigterm = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(sigterm) => sigterm,
+                Err(e) => {
+                    error!("Failed to install SIGTERM handler: {}", e);
+                    return;
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => info!("Received SIGINT, starting graceful shutdown"),
+                _ = sigterm.recv() => info!("Received SIGTERM, starting graceful shutdown"),
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                error!("Failed to listen for ctrl-c: {}", e);
+                return;
+            }
+            info!("Received ctrl-c, starting graceful shutdown");
+        }
+
+        shutdown.cancel();
+    });
+}
+
+// Spawn the introspection/control server for the current mode's state. Bound
+// on its own address so it stays up even if `METRICS_ADDR` is reused or
+// disabled, and so pause/resume calls aren't mixed in with the read-only
+// Prometheus scrape path.
+fn spawn_control_server(state: ControlServerState) {
+    let control_addr = env::var("CONTROL_ADDR").unwrap_or_else(|_| "0.0.0.0:9101".to_string());
+    tokio::spawn(async move {
+        if let Err(e) = run_control_server(&control_addr, state).await {
+            error!("Control server error: {}", e);
+        }
+    });
+}
+
 // Initialize wallet from environment variable
 fn init_wallet() -> Result<LocalWallet, Box<dyn std::error::Error + Send + Sync>> {
     let wallet_key = env::var("HYPERLIQUID_WALLET_KEY")
@@ -103,244 +188,248 @@ fn init_wallet() -> Result<LocalWallet, Box<dyn std::error::Error + Send + Sync>
     Ok(wallet)
 }
 
-// Initialize Redis connection
-async fn init_redis() -> Result<RedisClient, Box<dyn std::error::Error + Send + Sync>> {
+// Initialize the Redis connection and the pool shared by every service, so
+// a transient outage or a single service's burst of traffic can't dial
+// Redis unboundedly or wedge another service's connection.
+async fn init_redis() -> Result<(RedisClient, RedisPool), Box<dyn std::error::Error + Send + Sync>> {
     let redis_url = env::var("REDIS_URL").unwrap_or_else(|_| "redis://redis:6379".to_string());
     info!("Connecting to Redis at {}", redis_url);
-    
+
     let client = redis::Client::open(redis_url)?;
-    
+
     // Test connection
     let mut conn = client.get_async_connection().await?;
     redis::cmd("PING").query_async::<_, ()>(&mut conn).await?;
     info!("Successfully connected to Redis");
-    
-    Ok(client)
+
+    let pool_max_size = env::var("REDIS_POOL_MAX_SIZE")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_POOL_MAX_SIZE);
+    let pool_connection_timeout = env::var("REDIS_POOL_CONNECTION_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_POOL_CONNECTION_TIMEOUT);
+
+    let pool = create_redis_pool(client.clone(), pool_max_size, pool_connection_timeout).await?;
+    info!("Built shared Redis connection pool (max_size={}, connection_timeout={:?})", pool_max_size, pool_connection_timeout);
+
+    Ok((client, pool))
 }
 
 // Run the symbol scanner service
-async fn run_symbol_scanner_service(redis_client: RedisClient) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn run_symbol_scanner_service(redis_pool: RedisPool, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Shared state for symbol metrics
     let symbol_metrics = Arc::new(RwLock::new(HashMap::new()));
-    
+
+    spawn_control_server(ControlServerState::SymbolScanner { symbol_metrics: symbol_metrics.clone() });
+
     // Create and start the scanner
     let mut scanner = SymbolScanner::new(
-        redis_client.clone(),
+        redis_pool,
         symbol_metrics,
         Duration::from_secs(3600), // 1 hour
         10, // Top 10 symbols
         true, // Use testnet
     ).await?;
-    
+
     scanner.start().await?;
-    
-    // Keep the task running
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-    }
+
+    // Keep the task running until a shutdown signal arrives
+    shutdown.cancelled().await;
+    info!("Symbol scanner service shutting down");
+    Ok(())
 }
 
 // Run the config service
-async fn run_config_service(redis_client: RedisClient) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn run_config_service(redis_client: RedisClient, redis_pool: RedisPool, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Shared state for configs
     let configs = Arc::new(RwLock::new(HashMap::new()));
-    
+
+    spawn_control_server(ControlServerState::ConfigService { configs: configs.clone() });
+
     // Create the config service
     let config_service = ConfigService::new(
+        redis_pool,
         redis_client,
         configs,
         "mm_config".to_string(),
     );
-    
+
     // Load any stored configurations
     if let Err(e) = config_service.load_stored_configs().await {
         error!("Failed to load stored configurations: {}", e);
     }
-    
-    // Start listening for configuration updates
-    config_service.start().await?;
-    
-    // Keep the task running
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // Start listening for configuration updates; returns once `shutdown` fires
+    config_service.start(shutdown).await?;
+    Ok(())
+}
+
+// Build the set of history sinks position updates are durably recorded to.
+// The Redis stream is always present; a Postgres sink is added on top when
+// POSTGRES_URL is configured, for SQL-queryable PnL/exposure history.
+async fn build_history_sinks(redis_pool: RedisPool) -> Vec<Arc<dyn HistorySink>> {
+    let mut sinks: Vec<Arc<dyn HistorySink>> = vec![Arc::new(RedisStreamSink::new(redis_pool))];
+
+    if let Ok(postgres_url) = env::var("POSTGRES_URL") {
+        match PostgresSink::new(&postgres_url).await {
+            Ok(sink) => sinks.push(Arc::new(sink)),
+            Err(e) => error!("Failed to connect Postgres history sink: {}", e),
+        }
     }
+
+    sinks
 }
 
 // Run the position manager service
-async fn run_position_manager_service(redis_client: RedisClient) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn run_position_manager_service(redis_pool: RedisPool, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Shared state for positions
     let positions = Arc::new(RwLock::new(HashMap::new()));
-    
+
+    spawn_control_server(ControlServerState::PositionManager { positions: positions.clone() });
+
+    let history_sinks = build_history_sinks(redis_pool.clone()).await;
+
     // Create the position manager
     let position_manager = PositionManager::new(
-        redis_client,
+        redis_pool,
         positions,
         Duration::from_secs(1), // Update every second
         "mm_position_updates".to_string(),
+        history_sinks,
+        Arc::new(RwLock::new(HashMap::new())),
     );
-    
-    // Start the position manager
-    position_manager.start().await?;
-    
-    // Keep the task running (should never return from start() in normal operation)
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-    }
+
+    // Start the position manager; returns once `shutdown` fires
+    position_manager.start(shutdown).await?;
+    Ok(())
 }
 
 // Run the market making service
-async fn run_market_maker_service(redis_client: RedisClient, wallet: LocalWallet) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn run_market_maker_service(
+    redis_client: RedisClient,
+    redis_pool: RedisPool,
+    wallet: LocalWallet,
+    shutdown: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Shared state
     let configs = Arc::new(RwLock::new(HashMap::new()));
     let positions = Arc::new(RwLock::new(HashMap::new()));
-    
+
     // Start the configuration service (market maker needs this component)
     let config_service_configs = configs.clone();
-    let redis_config = redis_client.clone();
+    let config_service_redis_client = redis_client.clone();
+    let config_service_redis_pool = redis_pool.clone();
+    let config_service_shutdown = shutdown.clone();
     tokio::spawn(async move {
         let config_service = ConfigService::new(
-            redis_config,
+            config_service_redis_pool,
+            config_service_redis_client,
             config_service_configs,
             "mm_config".to_string(),
         );
-        
+
         // Load any stored configurations
         if let Err(e) = config_service.load_stored_configs().await {
             error!("Failed to load stored configurations: {}", e);
         }
-        
-        // Start listening for configuration updates
-        if let Err(e) = config_service.start().await {
+
+        // Start listening for configuration updates; returns once `shutdown` fires
+        if let Err(e) = config_service.start(config_service_shutdown).await {
             error!("Configuration service error: {}", e);
         }
     });
-    
+
     // Start position manager (market maker needs this component)
     let position_manager_positions = positions.clone();
-    let redis_position = redis_client.clone();
+    let position_manager_redis_pool = redis_pool.clone();
+    let position_manager_shutdown = shutdown.clone();
     tokio::spawn(async move {
+        let history_sinks = build_history_sinks(position_manager_redis_pool.clone()).await;
+
         let position_manager = PositionManager::new(
-            redis_position,
+            position_manager_redis_pool,
             position_manager_positions,
             Duration::from_secs(1), // Update every second
             "mm_position_updates".to_string(),
+            history_sinks,
+            Arc::new(RwLock::new(HashMap::new())),
         );
-        
-        if let Err(e) = position_manager.start().await {
+
+        if let Err(e) = position_manager.start(position_manager_shutdown).await {
             error!("Position manager error: {}", e);
         }
     });
-    
-    // Run the market making engine with the Redis client
-    run_market_making_engine(configs, positions, wallet, redis_client.clone()).await?;
-    
-    // Keep the task running
-    loop {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-    }
+
+    // Run the market making engine; returns once `shutdown` fires and every
+    // symbol has been stopped (or the grace period ran out)
+    run_market_making_engine(configs, positions, wallet, redis_pool, redis_client, shutdown).await?;
+
+    Ok(())
 }
 
 // Run the market making engine
 async fn run_market_making_engine(
-    configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>, 
+    configs: Arc<RwLock<HashMap<String, MarketMakerConfig>>>,
     positions: Arc<RwLock<HashMap<String, Position>>>,
     wallet: LocalWallet,
+    redis_pool: RedisPool,
     redis_client: RedisClient,
+    shutdown: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("Starting Market Making Engine");
-    
-    // Map of active market makers by symbol
-    let mut market_makers: HashMap<String, Arc<RwLock<EnhancedMarketMaker>>> = HashMap::new();
-    
-    // Track the previous configuration to detect changes
-    let mut previous_configs: HashMap<String, MarketMakerConfig> = HashMap::new();
-    
+
+    // Drives each symbol's EnhancedMarketMaker through an explicit lifecycle
+    // state machine (spawn, restart-on-crash, backoff-on-init-failure,
+    // roll-on-config-change) instead of the inline new/update branching this
+    // loop used to do by hand. Shared behind a lock so the control server can
+    // read/mutate it (status, pause/resume) concurrently with this loop.
+    let lifecycle = Arc::new(RwLock::new(LifecycleManager::new(
+        wallet,
+        redis_pool,
+        redis_client.clone(),
+        positions,
+        shutdown.clone(),
+    )));
+
+    spawn_control_server(ControlServerState::MarketMaker { lifecycle: lifecycle.clone(), configs: configs.clone() });
+
+    // React to individual config updates the moment ConfigService publishes
+    // them, instead of waiting for the next periodic scan below.
+    let pubsub = ResilientPubSub::subscribe(redis_client, "mm_config".to_string());
+    let mut fallback = tokio::time::interval(RECONCILE_FALLBACK_INTERVAL);
+
     loop {
-        // Get current configs
-        let configs_read = configs.read().await;
-        info!("Checking for config updates. Current config count: {}", configs_read.len());
-        
-        // Check for new configs or updates
-        for (symbol, config) in configs_read.iter() {
-            // Check if this is a new config or an update to an existing one
-            let is_new = !previous_configs.contains_key(symbol);
-            let is_update = if let Some(prev_config) = previous_configs.get(symbol) {
-                // Compare configuration values to see if anything has changed
-                prev_config.daily_return_bps != config.daily_return_bps ||
-                prev_config.notional_per_side != config.notional_per_side ||
-                prev_config.daily_pnl_stop_loss != config.daily_pnl_stop_loss ||
-                prev_config.trailing_take_profit != config.trailing_take_profit ||
-                prev_config.trailing_stop_loss != config.trailing_stop_loss ||
-                prev_config.hedge_only_mode != config.hedge_only_mode ||
-                prev_config.force_quote_refresh_interval != config.force_quote_refresh_interval ||
-                prev_config.max_long_usd != config.max_long_usd ||
-                prev_config.max_short_usd != config.max_short_usd ||
-                prev_config.enable_trading != config.enable_trading
-            } else {
-                false
-            };
-            
-            if is_new {
-                info!("Found new configuration for symbol: {}", symbol);
-                info!("Config details: daily_return_bps={}, notional_per_side={}, interval={}", 
-                    config.daily_return_bps, config.notional_per_side, config.force_quote_refresh_interval);
-                    
-                // Create a new market maker for this symbol
-                info!("Creating new market maker for {}", symbol);
-                
-                match EnhancedMarketMaker::new(config.clone(), wallet.clone(), redis_client.clone()).await {
-                    Ok(market_maker) => {
-                        // Store the market maker in a shareable container
-                        let mm = Arc::new(RwLock::new(market_maker));
-                        
-                        // Start the market maker in a separate task
-                        let symbol_clone = symbol.clone();
-                        let positions_clone = positions.clone();
-                        let mm_clone = mm.clone();
-                        
-                        tokio::spawn(async move {
-                            // Get mutable reference through the lock
-                            let mut mm_lock = mm_clone.write().await;
-                            
-                            if let Err(e) = mm_lock.start().await {
-                                error!("Market maker error for {}: {}", symbol_clone, e);
-                            }
-                        });
-                        
-                        market_makers.insert(symbol.clone(), mm);
-                        
-                        // Store the config for future comparison
-                        previous_configs.insert(symbol.clone(), config.clone());
-                    },
-                    Err(e) => {
-                        error!("Failed to create market maker for {}: {}", symbol, e);
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                info!("Market making engine shutting down, stopping all symbols");
+                lifecycle.write().await.shutdown(SHUTDOWN_GRACE_PERIOD).await;
+                return Ok(());
+            }
+            payload = pubsub.recv() => {
+                match ConfigMessage::parse(&payload) {
+                    Ok(ConfigMessage::Update(config)) => {
+                        let symbol = config.symbol.clone();
+                        info!("Engine received config update for {} via pub/sub", symbol);
+                        configs.write().await.insert(symbol.clone(), config.clone());
+                        lifecycle.write().await.reconcile_one(&symbol, &config).await;
                     }
+                    Ok(ConfigMessage::Delete(symbol)) => {
+                        info!("Engine received config delete for {} via pub/sub, tearing down its instance", symbol);
+                        configs.write().await.remove(&symbol);
+                        lifecycle.write().await.remove_symbol(&symbol).await;
+                    }
+                    Err(e) => error!("Engine failed to parse config update from mm_config: {}", e),
                 }
-            } else if is_update {
-                info!("Found updated configuration for symbol: {}", symbol);
-                info!("New config values: daily_return_bps={}, notional_per_side={}, interval={}", 
-                    config.daily_return_bps, config.notional_per_side, config.force_quote_refresh_interval);
-                
-                // Debug dump of the full config to verify nothing is being lost
-                info!("FULL CONFIG UPDATE - Symbol: {}, daily_return_bps: {}, notional_per_side: {}, hedge_mode: {}, max_long: {}, max_short: {}, enable_trading: {}",
-                     config.symbol, config.daily_return_bps, config.notional_per_side, 
-                     config.hedge_only_mode, config.max_long_usd, config.max_short_usd, config.enable_trading);
-                
-                // Update existing market maker config
-                if let Some(market_maker) = market_makers.get(symbol) {
-                    info!("Updating existing market maker for {}", symbol);
-                    let mut mm = market_maker.write().await;
-                    mm.update_config(config.clone());
-                    
-                    // Store the updated config for future comparison
-                    previous_configs.insert(symbol.clone(), config.clone());
-                } else {
-                    warn!("Found config update for {} but no market maker instance exists", symbol);
-                }
+            }
+            _ = fallback.tick() => {
+                let configs_read = configs.read().await;
+                info!("Periodic reconciliation fallback. Current config count: {}", configs_read.len());
+                lifecycle.write().await.reconcile(&configs_read).await;
+                drop(configs_read);
             }
         }
-        
-        // Wait before checking for updates again
-        tokio::time::sleep(Duration::from_secs(1)).await;
     }
 }
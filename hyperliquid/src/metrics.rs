@@ -0,0 +1,110 @@
+use std::sync::Once;
+
+use lazy_static::lazy_static;
+use log::info;
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+
+lazy_static! {
+    static ref REGISTRY: Registry = Registry::new();
+
+    /// Number of symbols currently tracked by the scanner
+    pub static ref SYMBOLS_TRACKED: IntGauge =
+        IntGauge::new("symbols_tracked", "Number of symbols currently tracked by the scanner").unwrap();
+
+    /// Duration of a full-universe scan
+    pub static ref SCAN_DURATION_SECONDS: Histogram = Histogram::with_opts(
+        HistogramOpts::new("scan_duration_seconds", "Duration in seconds of a full scan_symbols pass")
+    ).unwrap();
+
+    /// Redis write failures observed across the scanner and config service
+    pub static ref REDIS_WRITE_ERRORS_TOTAL: IntCounter =
+        IntCounter::new("redis_write_errors_total", "Total Redis write errors").unwrap();
+
+    /// API calls made to the Hyperliquid info endpoint, labeled by which call
+    pub static ref API_CALLS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("api_calls_total", "Total Hyperliquid API calls"),
+        &["endpoint"],
+    ).unwrap();
+
+    /// Latest 24h volume for each of the top-N tracked symbols
+    pub static ref SYMBOL_VOLUME_24H: GaugeVec = GaugeVec::new(
+        Opts::new("symbol_volume_24h", "24h volume in USD for a tracked symbol"),
+        &["symbol"],
+    ).unwrap();
+
+    /// Configuration updates successfully applied by ConfigService
+    pub static ref CONFIG_UPDATES_TOTAL: IntCounter =
+        IntCounter::new("config_updates_total", "Total configuration updates applied").unwrap();
+
+    /// Configuration updates rejected by `validate_config`
+    pub static ref CONFIG_VALIDATION_FAILURES_TOTAL: IntCounter = IntCounter::new(
+        "config_validation_failures_total",
+        "Total configuration updates rejected by validation",
+    ).unwrap();
+
+    /// Time spent JSON-serializing a position summary in `publish_position_updates`
+    pub static ref POSITION_PUBLISH_SERIALIZE_SECONDS: Histogram = Histogram::with_opts(
+        HistogramOpts::new("position_publish_serialize_seconds", "Duration in seconds spent serializing a position summary")
+    ).unwrap();
+
+    /// Time spent on the Redis publish round-trip in `publish_position_updates`
+    pub static ref POSITION_PUBLISH_REDIS_SECONDS: Histogram = Histogram::with_opts(
+        HistogramOpts::new("position_publish_redis_seconds", "Duration in seconds spent publishing a position summary to Redis")
+    ).unwrap();
+
+    /// Number of positions included in the most recently published summary
+    pub static ref POSITION_PUBLISH_COUNT: IntGauge =
+        IntGauge::new("position_publish_count", "Number of positions in the most recently published summary").unwrap();
+}
+
+static REGISTER_ONCE: Once = Once::new();
+
+/// Register every metric with the global registry. Safe to call more than
+/// once; registration only happens on the first call.
+pub fn register_metrics() {
+    REGISTER_ONCE.call_once(|| {
+        REGISTRY.register(Box::new(SYMBOLS_TRACKED.clone())).expect("register symbols_tracked");
+        REGISTRY.register(Box::new(SCAN_DURATION_SECONDS.clone())).expect("register scan_duration_seconds");
+        REGISTRY.register(Box::new(REDIS_WRITE_ERRORS_TOTAL.clone())).expect("register redis_write_errors_total");
+        REGISTRY.register(Box::new(API_CALLS_TOTAL.clone())).expect("register api_calls_total");
+        REGISTRY.register(Box::new(SYMBOL_VOLUME_24H.clone())).expect("register symbol_volume_24h");
+        REGISTRY.register(Box::new(CONFIG_UPDATES_TOTAL.clone())).expect("register config_updates_total");
+        REGISTRY.register(Box::new(CONFIG_VALIDATION_FAILURES_TOTAL.clone())).expect("register config_validation_failures_total");
+        REGISTRY.register(Box::new(POSITION_PUBLISH_SERIALIZE_SECONDS.clone())).expect("register position_publish_serialize_seconds");
+        REGISTRY.register(Box::new(POSITION_PUBLISH_REDIS_SECONDS.clone())).expect("register position_publish_redis_seconds");
+        REGISTRY.register(Box::new(POSITION_PUBLISH_COUNT.clone())).expect("register position_publish_count");
+    });
+}
+
+/// Render the current state of every registered metric in the Prometheus text format
+pub fn gather_metrics_text() -> String {
+    let metric_families = REGISTRY.gather();
+    let mut buffer = Vec::new();
+    let encoder = TextEncoder::new();
+    encoder.encode(&metric_families, &mut buffer).expect("encode metrics");
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Serve the registered metrics over HTTP at `/metrics` on `bind_addr` (e.g. `0.0.0.0:9100`)
+pub async fn run_metrics_server(bind_addr: &str) -> std::io::Result<()> {
+    use actix_web::{get, App, HttpResponse, HttpServer};
+
+    register_metrics();
+
+    #[get("/metrics")]
+    async fn metrics_handler() -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(gather_metrics_text())
+    }
+
+    info!("Starting Prometheus metrics server on {}", bind_addr);
+
+    HttpServer::new(|| App::new().service(metrics_handler))
+        .bind(bind_addr)?
+        .run()
+        .await
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Bounded so a slow/absent subscriber can't grow memory unbounded; a lagged
+/// receiver just misses the oldest events and resumes from the snapshot, so
+/// dropping some history here is safe by design.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// What changed in one position/PnL update: a human-readable description
+/// plus the numeric deltas, so a downstream consumer can react to the change
+/// itself (e.g. alert on a large single realized loss) without having to
+/// diff two snapshots.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionDelta {
+    pub filled_qty: f64,
+    pub fill_price: Option<f64>,
+    pub realized_pnl_delta: f64,
+    pub description: String,
+}
+
+/// The full reference state at the time of an update, so a consumer that
+/// missed earlier events (a new subscriber, or one that lagged) can still
+/// reason about where things stand without replaying history.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionSnapshot {
+    pub size: f64,
+    pub entry_price: f64,
+    pub unrealized_pnl: f64,
+    pub realized_pnl: f64,
+    pub realized_daily_pnl: f64,
+    pub highest_pnl: f64,
+    pub lowest_pnl: f64,
+}
+
+/// One position/PnL change, published whenever a fill or a user-state poll
+/// moves size, entry price, unrealized PnL, or realized PnL for `symbol`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionUpdateEvent {
+    pub symbol: String,
+    pub timestamp_ms: u64,
+    pub delta: PositionDelta,
+    pub snapshot: PositionSnapshot,
+}
+
+pub type PositionUpdateSender = broadcast::Sender<PositionUpdateEvent>;
+pub type PositionUpdateReceiver = broadcast::Receiver<PositionUpdateEvent>;
+
+/// Create the broadcast channel `EnhancedMarketMaker` publishes position/PnL
+/// updates on. Call `.subscribe()` on the returned sender (or on
+/// `EnhancedMarketMaker::subscribe_position_updates`) from each downstream
+/// consumer - a dashboard, a supervising process - so multiple symbols/bots
+/// can be watched live without polling `self.positions`.
+pub fn position_update_channel() -> PositionUpdateSender {
+    let (sender, _receiver) = broadcast::channel(CHANNEL_CAPACITY);
+    sender
+}
@@ -1,132 +1,251 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 use log::{info, error, warn, debug};
-use redis::{Client as RedisClient, AsyncCommands};
+use redis::AsyncCommands;
+use tokio_util::sync::CancellationToken;
 
 use crate::enhanced_market_maker::Position;
+use crate::redis_pool::RedisPool;
+use crate::history_sink::HistorySink;
+use crate::metrics;
 
 /// Position summary with aggregated metrics
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PositionSummary {
     pub timestamp: u64,
+    /// Monotonically increasing per-publish sequence number, so consumers
+    /// of the `position_history` stream can order messages and detect gaps
+    pub seq: u64,
     pub positions: Vec<Position>,
     pub total_pnl: f64,
+    pub total_realized_pnl: f64,
     pub total_long_exposure: f64,
     pub total_short_exposure: f64,
 }
 
+/// Which side of the book a fill occurred on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillSide {
+    Buy,
+    Sell,
+}
+
+/// Per-symbol scaling needed to convert native (on-chain/exchange) integer
+/// amounts into human-scaled UI units before any PnL or notional math runs.
+#[derive(Clone, Copy, Debug)]
+pub struct InstrumentMetadata {
+    /// Decimal places the base asset's native size is scaled by
+    pub base_decimals: u32,
+    /// Decimal places the quote asset's native price is scaled by
+    pub quote_decimals: u32,
+    /// Multiplier applied to a single contract's UI size, e.g. for
+    /// instruments quoted per-lot rather than per-unit
+    pub contract_multiplier: f64,
+}
+
+impl InstrumentMetadata {
+    /// No scaling applied: native amounts are already UI-scaled
+    pub fn identity() -> Self {
+        InstrumentMetadata { base_decimals: 0, quote_decimals: 0, contract_multiplier: 1.0 }
+    }
+
+    fn native_to_ui_size(&self, native_size: f64) -> f64 {
+        (native_size / 10f64.powi(self.base_decimals as i32)) * self.contract_multiplier
+    }
+
+    fn native_to_ui_price(&self, native_price: f64) -> f64 {
+        native_price / 10f64.powi(self.quote_decimals as i32)
+    }
+}
+
 /// Position Manager for tracking and updating positions
 pub struct PositionManager {
-    redis_client: RedisClient,
+    redis_pool: RedisPool,
     positions: Arc<RwLock<HashMap<String, Position>>>,
     update_interval: Duration,
     update_channel: String,
+    seq: AtomicU64,
+    history_sinks: Vec<Arc<dyn HistorySink>>,
+    instrument_metadata: Arc<RwLock<HashMap<String, InstrumentMetadata>>>,
 }
 
 impl PositionManager {
-    /// Create a new position manager
+    /// Create a new position manager backed by a pooled, multiplexed Redis
+    /// connection so the hot publish path reuses a live connection instead
+    /// of opening a fresh one every tick. Every published summary is handed
+    /// to each of `history_sinks` in turn, so durable storage is a matter of
+    /// adding a `HistorySink` rather than hard-wiring another write here.
     pub fn new(
-        redis_client: RedisClient,
+        redis_pool: RedisPool,
         positions: Arc<RwLock<HashMap<String, Position>>>,
         update_interval: Duration,
         update_channel: String,
+        history_sinks: Vec<Arc<dyn HistorySink>>,
+        instrument_metadata: Arc<RwLock<HashMap<String, InstrumentMetadata>>>,
     ) -> Self {
         PositionManager {
-            redis_client,
+            redis_pool,
             positions,
             update_interval,
             update_channel,
+            seq: AtomicU64::new(0),
+            history_sinks,
+            instrument_metadata,
         }
     }
-    
-    /// Start the position manager
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+
+    /// Register (or replace) the native->UI scaling for `symbol`. Symbols
+    /// with no registered metadata are treated as already UI-scaled.
+    pub async fn set_instrument_metadata(&self, symbol: &str, metadata: InstrumentMetadata) {
+        self.instrument_metadata.write().await.insert(symbol.to_string(), metadata);
+    }
+
+    async fn metadata_for(&self, symbol: &str) -> InstrumentMetadata {
+        self.instrument_metadata
+            .read()
+            .await
+            .get(symbol)
+            .copied()
+            .unwrap_or_else(InstrumentMetadata::identity)
+    }
+
+    /// Start the position manager. Returns once `shutdown` is cancelled.
+    pub async fn start(&self, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Starting Position Manager");
-        info!("Publishing position updates every {:?} to channel: {}", 
+        info!("Publishing position updates every {:?} to channel: {}",
             self.update_interval, self.update_channel);
-        
+
         loop {
             // Publish position updates
             if let Err(e) = self.publish_position_updates().await {
                 error!("Failed to publish position updates: {}", e);
             }
-            
-            // Wait for next update interval
-            tokio::time::sleep(self.update_interval).await;
+
+            // Wait for next update interval, or shut down early
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Position manager shutting down");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(self.update_interval) => {}
+            }
         }
     }
     
-    /// Publish position updates to Redis
-    async fn publish_position_updates(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Publish position updates to Redis. `pub` (rather than private) so the
+    /// `benches/position_manager_bench.rs` criterion harness can drive this
+    /// hot path directly with synthetic position maps.
+    pub async fn publish_position_updates(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Read current positions
         let positions_read = self.positions.read().await;
-        
+
         // Skip if no positions
         if positions_read.is_empty() {
             return Ok(());
         }
-        
+
         // Calculate summary metrics
         let mut total_pnl = 0.0;
+        let mut total_realized_pnl = 0.0;
         let mut total_long_exposure = 0.0;
         let mut total_short_exposure = 0.0;
-        
+
         let positions: Vec<Position> = positions_read.values().cloned().collect();
-        
+        let position_count = positions.len();
+
         for position in &positions {
             total_pnl += position.unrealized_pnl;
-            
+            total_realized_pnl += position.realized_pnl;
+
             if position.size > 0.0 {
                 total_long_exposure += position.notional_usd;
             } else if position.size < 0.0 {
                 total_short_exposure += position.notional_usd;
             }
         }
-        
+
         // Create summary
         let summary = PositionSummary {
             timestamp: current_timestamp_ms(),
+            seq: self.seq.fetch_add(1, Ordering::Relaxed) + 1,
             positions,
             total_pnl,
+            total_realized_pnl,
             total_long_exposure,
             total_short_exposure,
         };
-        
-        // Convert to JSON
+
+        // Convert to JSON, timing how long serialization takes
+        let serialize_timer = metrics::POSITION_PUBLISH_SERIALIZE_SECONDS.start_timer();
         let summary_json = serde_json::to_string(&summary)?;
-        
-        // Get Redis connection
-        let mut conn = self.redis_client.get_async_connection().await?;
-        
-        // Publish to Redis channel
+        serialize_timer.observe_duration();
+
+        // Get a pooled, multiplexed connection instead of dialing Redis fresh
+        let mut conn = self.redis_pool.get().await?;
+
+        // Publish to Redis channel for live subscribers, timing the round-trip
+        let redis_timer = metrics::POSITION_PUBLISH_REDIS_SECONDS.start_timer();
         conn.publish(&self.update_channel, &summary_json).await?;
-        
-        // Add to Redis stream for history
-        let stream_data = vec![("data", summary_json.clone())];
-        conn.xadd("position_history", "*", &stream_data).await?;
-        
-        debug!("Published position update with {} positions", summary.positions.len());
-        
+        redis_timer.observe_duration();
+        drop(conn);
+
+        // Fan out to every configured durable history sink
+        for sink in &self.history_sinks {
+            if let Err(e) = sink.record(&summary).await {
+                error!("History sink failed to record position summary: {}", e);
+            }
+        }
+
+        metrics::POSITION_PUBLISH_COUNT.set(position_count as i64);
+        debug!("Published position update with {} positions", position_count);
+
         Ok(())
     }
-    
-    /// Update position information
+
+    /// Update position information from a feed update sourced at `source_ts`
+    /// (ms). `native_size`, `native_entry_price`, and `native_current_price`
+    /// are raw on-chain/exchange integer amounts; they are converted to UI
+    /// units via the symbol's registered `InstrumentMetadata` (identity if
+    /// none is registered) before any PnL or notional math runs. A slow or
+    /// retried update can arrive after a newer one already landed, so writes
+    /// not newer than the stored `last_update_ts` for that symbol are skipped
+    /// rather than clobbering the newer state.
     pub async fn update_position(
         &self,
         symbol: &str,
-        size: f64,
-        entry_price: f64,
-        current_price: f64,
+        native_size: f64,
+        native_entry_price: f64,
+        native_current_price: f64,
+        source_ts: u64,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut positions_write = self.positions.write().await;
-        
+
+        if let Some(existing) = positions_write.get(symbol) {
+            if source_ts <= existing.last_update_ts {
+                warn!(
+                    "Skipping out-of-order position update for {}: source_ts={} <= last_update_ts={}",
+                    symbol, source_ts, existing.last_update_ts
+                );
+                return Ok(());
+            }
+        }
+
+        let previous_version = positions_write.get(symbol).map(|p| p.version).unwrap_or(0);
+        let realized_pnl = positions_write.get(symbol).map(|p| p.realized_pnl).unwrap_or(0.0);
+
+        let metadata = self.metadata_for(symbol).await;
+        let size = metadata.native_to_ui_size(native_size);
+        let entry_price = metadata.native_to_ui_price(native_entry_price);
+        let current_price = metadata.native_to_ui_price(native_current_price);
+
         // Calculate PnL and notional value
         let unrealized_pnl = (current_price - entry_price) * size;
         let notional_usd = current_price.abs() * size.abs();
-        
+
         // Update or create position
         let position = Position {
             symbol: symbol.to_string(),
@@ -135,10 +254,104 @@ impl PositionManager {
             current_price,
             unrealized_pnl,
             notional_usd,
+            version: previous_version + 1,
+            last_update_ts: source_ts,
+            realized_pnl,
         };
-        
+
         positions_write.insert(symbol.to_string(), position);
-        
+
+        Ok(())
+    }
+
+    /// Apply a fill to the stored position for `symbol`, maintaining a
+    /// volume-weighted average `entry_price` and realizing PnL on whatever
+    /// quantity the fill closes - mirroring how a real fills feed, rather
+    /// than a full position snapshot, updates exposure.
+    ///
+    /// Same-direction fills (including opening from flat) roll into the
+    /// average entry price:
+    /// `entry_price = (entry_price*|size| + fill_price*|fill_qty|) / (|size| + |fill_qty|)`.
+    /// Opposite-direction fills realize `(fill_price - entry_price) * closed_qty * sign(size)`
+    /// on the overlapping quantity. A fill that closes the position exactly
+    /// resets the average entry price to zero; one that flips it through
+    /// zero carries the remainder as a new lot opened at `fill_price`.
+    ///
+    /// `native_fill_qty`, `native_fill_price`, and `native_current_price`
+    /// are raw native amounts, converted to UI units via the symbol's
+    /// registered `InstrumentMetadata` before any of the above runs.
+    pub async fn apply_fill(
+        &self,
+        symbol: &str,
+        native_fill_qty: f64,
+        native_fill_price: f64,
+        side: FillSide,
+        native_current_price: f64,
+        source_ts: u64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let metadata = self.metadata_for(symbol).await;
+        let fill_qty = metadata.native_to_ui_size(native_fill_qty);
+        let fill_price = metadata.native_to_ui_price(native_fill_price);
+        let current_price = metadata.native_to_ui_price(native_current_price);
+
+        let mut positions_write = self.positions.write().await;
+
+        let signed_fill_qty = match side {
+            FillSide::Buy => fill_qty.abs(),
+            FillSide::Sell => -fill_qty.abs(),
+        };
+
+        let (previous_size, mut entry_price, mut realized_pnl, previous_version) =
+            match positions_write.get(symbol) {
+                Some(p) => (p.size, p.entry_price, p.realized_pnl, p.version),
+                None => (0.0, 0.0, 0.0, 0),
+            };
+
+        let size = if previous_size == 0.0 || previous_size.signum() == signed_fill_qty.signum() {
+            // Opening or adding to the position in the same direction: roll
+            // the fill into the volume-weighted average entry price.
+            let new_size = previous_size + signed_fill_qty;
+            entry_price = (entry_price * previous_size.abs() + fill_price * signed_fill_qty.abs())
+                / new_size.abs();
+            new_size
+        } else {
+            // Opposite direction: the fill closes some, all, or more than
+            // the existing position, realizing PnL on the overlap.
+            let closed_qty = signed_fill_qty.abs().min(previous_size.abs());
+            realized_pnl += (fill_price - entry_price) * closed_qty * previous_size.signum();
+
+            let new_size = previous_size + signed_fill_qty;
+            if new_size.abs() < f64::EPSILON {
+                // Exact flat: reset the cost basis entirely.
+                entry_price = 0.0;
+                0.0
+            } else if new_size.signum() != previous_size.signum() {
+                // Flipped through zero: the remainder opens a new lot at
+                // the fill price.
+                entry_price = fill_price;
+                new_size
+            } else {
+                new_size
+            }
+        };
+
+        let unrealized_pnl = (current_price - entry_price) * size;
+        let notional_usd = current_price.abs() * size.abs();
+
+        let position = Position {
+            symbol: symbol.to_string(),
+            size,
+            entry_price,
+            current_price,
+            unrealized_pnl,
+            notional_usd,
+            version: previous_version + 1,
+            last_update_ts: source_ts,
+            realized_pnl,
+        };
+
+        positions_write.insert(symbol.to_string(), position);
+
         Ok(())
     }
 }
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+/// A pluggable source of an independent reference mid price for a symbol,
+/// so `EnhancedMarketMaker` can quote Hyperliquid off a more liquid external
+/// book rather than purely off Hyperliquid's own `current_mid_price`.
+/// Implementations typically wrap a websocket connection to an external
+/// venue (e.g. a Kraken-style ticker feed) running in a background task.
+#[async_trait]
+pub trait RatePriceSource: Send + Sync {
+    /// Latest known reference mid for `symbol`, or `None` if no update has
+    /// been observed yet.
+    async fn reference_mid(&self, symbol: &str) -> Option<f64>;
+}
+
+/// A `RatePriceSource` fed by an external adapter task calling `update()`
+/// whenever the upstream venue's ticker moves, e.g. from a websocket loop
+/// parsing best bid/ask out of a Kraken-style feed.
+pub struct ExternalTickerSource {
+    symbol: String,
+    mid: Arc<RwLock<Option<f64>>>,
+}
+
+impl ExternalTickerSource {
+    pub fn new(symbol: String) -> Self {
+        ExternalTickerSource { symbol, mid: Arc::new(RwLock::new(None)) }
+    }
+
+    /// Push a new best bid/ask observed from the external venue; the
+    /// reference mid is their midpoint.
+    pub async fn update_from_bid_ask(&self, bid: f64, ask: f64) {
+        let mut mid = self.mid.write().await;
+        *mid = Some((bid + ask) / 2.0);
+    }
+}
+
+#[async_trait]
+impl RatePriceSource for ExternalTickerSource {
+    async fn reference_mid(&self, symbol: &str) -> Option<f64> {
+        if symbol != self.symbol {
+            return None;
+        }
+        *self.mid.read().await
+    }
+}
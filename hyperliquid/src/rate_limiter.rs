@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Shared async token-bucket limiter. Up to `capacity` tokens may be
+/// taken at once from a full bucket, and a background task refills one token every
+/// `1 / tokens_per_sec` seconds, so sustained throughput across every caller
+/// is bounded to `tokens_per_sec` regardless of how much concurrency is
+/// fanned out on top of it.
+#[derive(Clone)]
+pub struct TokenBucket {
+    semaphore: Arc<Semaphore>,
+    available: Arc<AtomicUsize>,
+    capacity: usize,
+}
+
+impl TokenBucket {
+    /// Create a bucket that allows `tokens_per_sec` requests/sec on average,
+    /// bursting up to `capacity` requests at once.
+    pub fn new(tokens_per_sec: u64, capacity: usize) -> Self {
+        let semaphore = Arc::new(Semaphore::new(capacity));
+        let available = Arc::new(AtomicUsize::new(capacity));
+        let refill_interval = Duration::from_millis(1000 / tokens_per_sec.max(1));
+
+        let refill_semaphore = semaphore.clone();
+        let refill_available = available.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(refill_interval);
+            loop {
+                interval.tick().await;
+                if refill_available.load(Ordering::Relaxed) < capacity {
+                    refill_available.fetch_add(1, Ordering::Relaxed);
+                    refill_semaphore.add_permits(1);
+                }
+            }
+        });
+
+        TokenBucket { semaphore, available, capacity }
+    }
+
+    /// Acquire a single token, waiting for a refill if the bucket is empty.
+    pub async fn acquire(&self) {
+        let permit = self.semaphore.acquire().await.expect("token bucket semaphore is never closed");
+        // The refill task is the only thing that should hand permits back
+        // out, so forget this one rather than returning it to the semaphore.
+        permit.forget();
+        self.available.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// The most tokens this bucket holds, i.e. its burst size.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
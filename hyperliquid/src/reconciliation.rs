@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::{info, warn};
+use tokio::sync::Mutex;
+
+use hyperliquid_rust_sdk::bps_diff;
+
+use crate::enhanced_market_maker::EnhancedRestingOrder;
+use crate::exchange_backend::{BackendOrderStatus, ExchangeBackend};
+
+/// One quote the strategy wants resting on the book, independent of
+/// whatever is currently live. `level` is the ladder rung (1 is closest to
+/// mid, as in `QuoteLevel::level`), so each rung is reconciled against its own resting order rather
+/// than all orders on a side being treated as interchangeable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DesiredOrder {
+    pub is_bid: bool,
+    pub level: u16,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// An existing order whose price or size has drifted from what the strategy
+/// now wants. `new_order` supersedes `old_oid`, but `old_oid` must stay
+/// resting - Pending - until `new_order` is confirmed Resting; only then is
+/// it safe to cancel `old_oid` without leaving that side of the book empty.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplacementOrder {
+    pub old_oid: u64,
+    pub new_order: DesiredOrder,
+}
+
+/// Minimal diff between `desired` quotes and the currently resting orders:
+/// which existing orders are close enough to keep untouched, which must be
+/// cancelled outright (no longer desired at all), which new orders must be
+/// placed to cover a rung with nothing resting yet, and which existing
+/// orders must be replaced (superseded once their replacement is resting).
+#[derive(Clone, Debug, Default)]
+pub struct ReconciliationPlan {
+    pub keep: Vec<u64>,
+    pub cancel: Vec<u64>,
+    pub place: Vec<DesiredOrder>,
+    pub replace: Vec<ReplacementOrder>,
+}
+
+/// Diff `desired` against `active` (trigger markers excluded - they are not
+/// real quote orders) using a price tolerance in bps and a size epsilon,
+/// comparing against each order's `remaining()` unfilled size rather than
+/// its original size so a partial fill doesn't trigger a needless
+/// cancel-and-replace. Orders are matched by `(is_bid, level)` so each ladder
+/// rung is independently kept/modified/cancelled instead of any order on a
+/// side being treated as interchangeable with any other.
+pub fn diff_orders(
+    desired: &[DesiredOrder],
+    active: &HashMap<u64, EnhancedRestingOrder>,
+    price_tolerance_bps: i64,
+    size_epsilon: f64,
+) -> ReconciliationPlan {
+    let mut plan = ReconciliationPlan::default();
+    let mut matched: Vec<u64> = Vec::new();
+
+    for desired_order in desired {
+        let existing = active.values()
+            .filter(|o| o.trigger_kind.is_none() && o.is_bid == desired_order.is_bid && o.level == desired_order.level)
+            .find(|o| !matched.contains(&o.oid));
+
+        match existing {
+            Some(order) => {
+                matched.push(order.oid);
+                let price_changed = bps_diff(order.price, desired_order.price) > price_tolerance_bps;
+                let size_changed = (order.remaining() - desired_order.size).abs() > size_epsilon;
+
+                if price_changed || size_changed {
+                    plan.replace.push(ReplacementOrder { old_oid: order.oid, new_order: *desired_order });
+                } else {
+                    plan.keep.push(order.oid);
+                }
+            },
+            None => {
+                plan.place.push(*desired_order);
+            }
+        }
+    }
+
+    for (oid, order) in active.iter().filter(|(_, o)| o.trigger_kind.is_none()) {
+        if matched.contains(oid) {
+            continue;
+        }
+        let still_desired = desired.iter().any(|d| d.is_bid == order.is_bid && d.level == order.level);
+        if !still_desired {
+            plan.cancel.push(*oid);
+        }
+    }
+
+    plan
+}
+
+/// Execute a `ReconciliationPlan` against any `ExchangeBackend`, staged so a
+/// failed placement can never leave a side of the book empty. Generic over
+/// the backend so the same staging logic type-checks against either
+/// `ExchangeClient` or `SimulatedExchange`, but today only `EnhancedMarketMaker`
+/// calls it, always with the live `ExchangeClient` - `SimulatedExchange`
+/// crosses orders itself in `backtest::run_replay` rather than going through
+/// a reconciliation plan, so the generic has only ever been instantiated one
+/// way:
+///
+/// 1. Orders that are simply no longer desired (`plan.cancel`, no
+///    replacement waiting on them) are cancelled outright - there is nothing
+///    to stage, since nothing supersedes them.
+/// 2. Brand-new rungs (`plan.place`) and replacement orders (`plan.replace`)
+///    are placed together in one batch, Pending until the backend responds.
+/// 3. Only once a replacement comes back Resting is its superseded old order
+///    cancelled. If a replacement instead comes back Failed (or the whole
+///    batch errors), the old order it would have replaced is left exactly as
+///    it was - no cancel is issued for it - so the next reconcile simply
+///    re-diffs and retries rather than needing an explicit rollback.
+pub async fn execute_plan<B: ExchangeBackend>(
+    exchange_client: &B,
+    active_orders: &Arc<Mutex<HashMap<u64, EnhancedRestingOrder>>>,
+    symbol: &str,
+    plan: &ReconciliationPlan,
+) {
+    if !plan.cancel.is_empty() {
+        match exchange_client.bulk_cancel(symbol, plan.cancel.clone()).await {
+            Ok(()) => {
+                info!("Reconciliation: cancelled {} orders no longer desired for {}", plan.cancel.len(), symbol);
+                let mut active_orders_lock = active_orders.lock().await;
+                for oid in &plan.cancel {
+                    active_orders_lock.remove(oid);
+                }
+            },
+            Err(e) => {
+                warn!("Reconciliation: failed to cancel orders for {}: {}", symbol, e);
+            }
+        }
+    }
+
+    if plan.place.is_empty() && plan.replace.is_empty() {
+        return;
+    }
+
+    let mut to_place: Vec<DesiredOrder> = Vec::with_capacity(plan.place.len() + plan.replace.len());
+    to_place.extend(plan.place.iter().copied());
+    to_place.extend(plan.replace.iter().map(|r| r.new_order));
+
+    match exchange_client.bulk_order(symbol, to_place).await {
+        Ok(response) => {
+            let mut active_orders_lock = active_orders.lock().await;
+            let mut supersede: Vec<u64> = Vec::new();
+
+            for (index, status) in response.statuses.into_iter().enumerate() {
+                let replacement = index.checked_sub(plan.place.len()).and_then(|i| plan.replace.get(i));
+
+                match status {
+                    BackendOrderStatus::Resting { oid } => {
+                        let desired = replacement.map(|r| &r.new_order).or_else(|| plan.place.get(index));
+                        if let Some(desired) = desired {
+                            active_orders_lock.insert(oid, EnhancedRestingOrder {
+                                oid,
+                                position: desired.size,
+                                price: desired.price,
+                                is_bid: desired.is_bid,
+                                level: desired.level,
+                                trigger_kind: None,
+                                original_size: desired.size,
+                                filled_size: 0.0,
+                            });
+                            info!("Reconciliation: placed {} order id={} {}@{}",
+                                if desired.is_bid { "bid" } else { "ask" }, oid, desired.size, desired.price);
+                        }
+                        // Only now that the replacement is confirmed Resting is it
+                        // safe to cancel the order it supersedes.
+                        if let Some(r) = replacement {
+                            supersede.push(r.old_oid);
+                        }
+                    },
+                    BackendOrderStatus::Error(e) => {
+                        match replacement {
+                            Some(r) => warn!("Reconciliation: replacement for order id={} failed for {}: {}. Keeping existing order; will retry next reconcile.",
+                                r.old_oid, symbol, e),
+                            None => warn!("Reconciliation: new order placement failed for {}: {}", symbol, e),
+                        }
+                    }
+                }
+            }
+
+            drop(active_orders_lock);
+
+            if !supersede.is_empty() {
+                match exchange_client.bulk_cancel(symbol, supersede.clone()).await {
+                    Ok(()) => {
+                        let mut active_orders_lock = active_orders.lock().await;
+                        for oid in &supersede {
+                            active_orders_lock.remove(oid);
+                        }
+                        info!("Reconciliation: cancelled {} superseded orders for {}", supersede.len(), symbol);
+                    },
+                    Err(e) => {
+                        warn!("Reconciliation: failed to cancel superseded orders for {} after successful replacement: {}. \
+                               Both old and new orders may now be resting; next reconcile will re-diff.", symbol, e);
+                    }
+                }
+            }
+        },
+        Err(e) => {
+            warn!("Reconciliation: bulk order placement failed outright for {}: {}. Existing orders left untouched; will retry next reconcile.", symbol, e);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use redis::aio::ConnectionManager;
+use redis::{Client as RedisClient, RedisError};
+
+/// Default cap on simultaneous pooled connections when the operator hasn't
+/// set `REDIS_POOL_MAX_SIZE`.
+pub const DEFAULT_POOL_MAX_SIZE: u32 = 16;
+
+/// Default wait for a pooled connection (or a fresh one, under `max_size`)
+/// when the operator hasn't set `REDIS_POOL_CONNECTION_TIMEOUT_MS`.
+pub const DEFAULT_POOL_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `bb8::ManageConnection` impl that hands out multiplexed, auto-reconnecting
+/// Redis connections backed by `redis::aio::ConnectionManager` instead of
+/// opening a fresh TCP connection per checkout.
+pub struct RedisConnectionManager {
+    client: RedisClient,
+}
+
+impl RedisConnectionManager {
+    pub fn new(client: RedisClient) -> Self {
+        RedisConnectionManager { client }
+    }
+}
+
+#[async_trait::async_trait]
+impl bb8::ManageConnection for RedisConnectionManager {
+    type Connection = ConnectionManager;
+    type Error = RedisError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        self.client.get_tokio_connection_manager().await
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        redis::cmd("PING").query_async(conn).await
+    }
+
+    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
+        // ConnectionManager reconnects transparently, so a checked-out
+        // connection is never considered unrecoverably broken.
+        false
+    }
+}
+
+/// Shared, pooled handle to Redis.
+pub type RedisPool = bb8::Pool<RedisConnectionManager>;
+
+/// Build a connection pool from a plain `redis::Client`, capped at
+/// `max_size` simultaneous connections and waiting up to `connection_timeout`
+/// for one to become available.
+pub async fn create_redis_pool(
+    client: RedisClient,
+    max_size: u32,
+    connection_timeout: Duration,
+) -> Result<RedisPool, RedisError> {
+    bb8::Pool::builder()
+        .max_size(max_size)
+        .connection_timeout(connection_timeout)
+        .build(RedisConnectionManager::new(client))
+        .await
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures::StreamExt;
+use log::{error, warn};
+use redis::Client as RedisClient;
+use tokio::sync::{Mutex, Notify};
+
+/// How many messages the internal buffer holds before the oldest queued
+/// message is dropped to make room for the newest one.
+const DEFAULT_BUFFER_CAPACITY: usize = 256;
+
+/// Backoff before the first resubscribe attempt after a dropped pubsub stream.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Cap on the exponential backoff between resubscribe attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Fixed-capacity FIFO that drops the oldest entry instead of blocking the
+/// producer once it's full, so a slow consumer applies backpressure by
+/// losing the stalest data rather than stalling the Redis reader.
+struct DroppingBuffer {
+    queue: VecDeque<String>,
+    capacity: usize,
+}
+
+impl DroppingBuffer {
+    fn push(&mut self, item: String) -> Option<String> {
+        let dropped = if self.queue.len() >= self.capacity {
+            self.queue.pop_front()
+        } else {
+            None
+        };
+        self.queue.push_back(item);
+        dropped
+    }
+}
+
+/// An auto-reconnecting Redis pubsub subscription, decoupled from its
+/// consumer by a bounded internal buffer.
+///
+/// A background task owns the actual Redis pubsub stream: on termination or
+/// error it backs off exponentially and re-subscribes rather than letting
+/// the service silently stop receiving updates. Incoming payloads are
+/// pushed into a [`DroppingBuffer`] so a burst of messages, or a slow
+/// consumer, can never block the Redis reader — once the buffer is full the
+/// oldest queued payload is dropped and logged.
+pub struct ResilientPubSub {
+    buffer: std::sync::Arc<Mutex<DroppingBuffer>>,
+    notify: std::sync::Arc<Notify>,
+}
+
+impl ResilientPubSub {
+    /// Subscribe to `channel`, buffering up to the default capacity of
+    /// undelivered messages.
+    pub fn subscribe(redis_client: RedisClient, channel: String) -> Self {
+        Self::subscribe_with_capacity(redis_client, channel, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Subscribe to `channel`, buffering up to `buffer_capacity` undelivered
+    /// messages before the oldest is dropped to make room for the newest.
+    pub fn subscribe_with_capacity(redis_client: RedisClient, channel: String, buffer_capacity: usize) -> Self {
+        Self::spawn(redis_client, channel, buffer_capacity, false)
+    }
+
+    /// Pattern-subscribe (`PSUBSCRIBE`) to `pattern`, e.g. a keyspace
+    /// notification pattern like `__keyspace@0__:config:*`, buffering up to
+    /// the default capacity of undelivered messages.
+    pub fn psubscribe(redis_client: RedisClient, pattern: String) -> Self {
+        Self::spawn(redis_client, pattern, DEFAULT_BUFFER_CAPACITY, true)
+    }
+
+    fn spawn(redis_client: RedisClient, channel: String, buffer_capacity: usize, is_pattern: bool) -> Self {
+        let buffer = std::sync::Arc::new(Mutex::new(DroppingBuffer {
+            queue: VecDeque::new(),
+            capacity: buffer_capacity,
+        }));
+        let notify = std::sync::Arc::new(Notify::new());
+
+        let reader_buffer = buffer.clone();
+        let reader_notify = notify.clone();
+        tokio::spawn(async move {
+            let mut backoff = INITIAL_BACKOFF;
+
+            loop {
+                match run_subscription(&redis_client, &channel, is_pattern, &reader_buffer, &reader_notify).await {
+                    Ok(()) => {
+                        warn!("Pubsub stream for {} ended, resubscribing in {:?}", channel, backoff);
+                    }
+                    Err(e) => {
+                        error!("Pubsub stream for {} failed: {}, resubscribing in {:?}", channel, e, backoff);
+                    }
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        ResilientPubSub { buffer, notify }
+    }
+
+    /// Wait for and return the next buffered message.
+    pub async fn recv(&self) -> String {
+        loop {
+            {
+                let mut buffer = self.buffer.lock().await;
+                if let Some(item) = buffer.queue.pop_front() {
+                    return item;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Run a single subscription attempt until the stream ends or errors,
+/// pushing every payload received into the shared buffer.
+async fn run_subscription(
+    redis_client: &RedisClient,
+    channel: &str,
+    is_pattern: bool,
+    buffer: &std::sync::Arc<Mutex<DroppingBuffer>>,
+    notify: &std::sync::Arc<Notify>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let conn = redis_client.get_async_connection().await?;
+    let mut pubsub = conn.into_pubsub();
+    if is_pattern {
+        pubsub.psubscribe(channel).await?;
+    } else {
+        pubsub.subscribe(channel).await?;
+    }
+
+    // A successful (re)subscribe means the backoff for this attempt did its
+    // job; the caller still owns the backoff counter for the next failure.
+    let mut stream = pubsub.on_message();
+
+    while let Some(msg) = stream.next().await {
+        let payload: String = match msg.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to read pubsub payload on {}: {}", channel, e);
+                continue;
+            }
+        };
+
+        let mut buffer_guard = buffer.lock().await;
+        if let Some(dropped) = buffer_guard.push(payload) {
+            warn!("Pubsub buffer for {} full, dropped oldest queued update: {}", channel, dropped);
+        }
+        drop(buffer_guard);
+        notify.notify_one();
+    }
+
+    Ok(())
+}
@@ -3,15 +3,20 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
-use log::{info, error, warn, debug};
-use redis::{Client as RedisClient, AsyncCommands};
-use tokio::time::Instant;
+use log::{info, error, warn};
+use redis::AsyncCommands;
+use futures::stream::{self, StreamExt};
 use ethers::types::H160;
 
+use crate::redis_pool::RedisPool;
+use crate::candle_backfill::{self, Resolution};
+use crate::rate_limiter::TokenBucket;
+use crate::metrics;
+use crate::errors::ScannerError;
+
 use hyperliquid_rust_sdk::{
-    InfoClient, 
+    InfoClient,
     BaseUrl,
-    Error as SdkError,
     Meta,
     CandlesSnapshotResponse
 };
@@ -43,259 +48,459 @@ pub struct CandleData {
     pub price: f64,
 }
 
+/// Default TTL for a symbol's candle key, refreshed on every write
+const DEFAULT_CANDLE_TTL_SECS: usize = 24 * 60 * 60;
+
+/// Default TTL for the `top_symbols` snapshot
+const DEFAULT_TOP_SYMBOLS_TTL_SECS: usize = 2 * 60 * 60;
+
+/// Default sustained request budget against the API, shared across every
+/// concurrent scan
+const DEFAULT_RATE_LIMIT_TOKENS_PER_SEC: u64 = 10;
+
+/// How many symbols' candles may be in flight at once during a scan
+const DEFAULT_SCAN_CONCURRENCY: usize = 8;
+
+/// Starting backoff after a retryable scan failure, doubled on each
+/// consecutive failure up to `MAX_RETRY_BACKOFF`
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Cap on the backoff between retries of a failed scan
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
 /// Symbol Scanner service that periodically scans for new listings and tracks metrics
 pub struct SymbolScanner {
-    info_client: InfoClient,
-    redis_client: RedisClient,
+    info_client: Arc<InfoClient>,
+    redis_pool: RedisPool,
     metrics: Arc<RwLock<HashMap<String, SymbolMetrics>>>,
     scan_interval: Duration,
     top_n_symbols: usize,
-    last_api_call: Instant,
+    rate_limiter: TokenBucket,
+    scan_concurrency: usize,
+    candle_ttl_secs: usize,
+    top_symbols_ttl_secs: usize,
 }
 
 impl SymbolScanner {
-    /// Create a new symbol scanner
+    /// Create a new symbol scanner with the default candle/top-symbols retention windows
     pub async fn new(
-        redis_client: RedisClient,
+        redis_pool: RedisPool,
         metrics: Arc<RwLock<HashMap<String, SymbolMetrics>>>,
         scan_interval: Duration,
         top_n_symbols: usize,
         testnet: bool,
-    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Self, ScannerError> {
+        Self::new_with_ttls(
+            redis_pool,
+            metrics,
+            scan_interval,
+            top_n_symbols,
+            testnet,
+            DEFAULT_CANDLE_TTL_SECS,
+            DEFAULT_TOP_SYMBOLS_TTL_SECS,
+        )
+        .await
+    }
+
+    /// Create a new symbol scanner, letting the caller tune how long candle
+    /// data and the `top_symbols` snapshot survive in Redis before expiring
+    pub async fn new_with_ttls(
+        redis_pool: RedisPool,
+        metrics: Arc<RwLock<HashMap<String, SymbolMetrics>>>,
+        scan_interval: Duration,
+        top_n_symbols: usize,
+        testnet: bool,
+        candle_ttl_secs: usize,
+        top_symbols_ttl_secs: usize,
+    ) -> Result<Self, ScannerError> {
         // Create InfoClient to get market data
         let base_url = if testnet { Some(BaseUrl::Testnet) } else { Some(BaseUrl::Mainnet) };
         let info_client = InfoClient::new(None, base_url).await?;
         info!("base_url: {:?}", info_client.http_client.base_url);
-        
+
         Ok(SymbolScanner {
-            info_client,
-            redis_client,
+            info_client: Arc::new(info_client),
+            redis_pool,
             metrics,
             scan_interval,
             top_n_symbols,
-            last_api_call: Instant::now(),
+            rate_limiter: TokenBucket::new(DEFAULT_RATE_LIMIT_TOKENS_PER_SEC, DEFAULT_RATE_LIMIT_TOKENS_PER_SEC as usize),
+            scan_concurrency: DEFAULT_SCAN_CONCURRENCY,
+            candle_ttl_secs,
+            top_symbols_ttl_secs,
         })
     }
-    
+
     /// Start the scanner service
-    pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///
+    /// Retryable failures (Redis hiccups, SDK/rate-limit errors) back off
+    /// exponentially and retry rather than tearing down the service; a
+    /// fatal failure (bad config, a parse error that will never succeed) is
+    /// surfaced to the caller instead of being retried forever.
+    pub async fn start(&mut self) -> Result<(), ScannerError> {
         info!("Starting Symbol Scanner Service using REST APIs only");
-        info!("Monitoring top {} symbols with rate limit of 1 request per second", self.top_n_symbols);
-        
+        info!("Monitoring the full universe with a shared budget of {} requests/sec across up to {} concurrent symbols",
+            self.rate_limiter.capacity(), self.scan_concurrency);
+
+        let mut retry_backoff = INITIAL_RETRY_BACKOFF;
+
         // Main scanning loop
         loop {
             info!("Performing scheduled scan for symbol metrics");
-            if let Err(e) = self.scan_symbols().await {
-                error!("Failed to scan symbols: {}", e);
+            match self.scan_symbols().await {
+                Ok(()) => {
+                    retry_backoff = INITIAL_RETRY_BACKOFF;
+                    tokio::time::sleep(self.scan_interval).await;
+                }
+                Err(e) if e.is_retryable() => {
+                    warn!("Scan failed with a retryable error, backing off {:?}: {}", retry_backoff, e);
+                    tokio::time::sleep(retry_backoff).await;
+                    retry_backoff = (retry_backoff * 2).min(MAX_RETRY_BACKOFF);
+                }
+                Err(e) => {
+                    error!("Scan failed with a fatal error, stopping scanner: {}", e);
+                    return Err(e);
+                }
             }
-            
-            // Wait for the next scan interval
-            tokio::time::sleep(self.scan_interval).await;
         }
     }
     
-    /// Apply rate limit before making an API call
-    async fn apply_rate_limit(&mut self) {
-        // Calculate time since last API call
-        let elapsed = self.last_api_call.elapsed();
-        let one_second = Duration::from_secs(1);
-        
-        // If less than 1 second has passed, sleep for the remaining time
-        if elapsed < one_second {
-            let sleep_duration = one_second - elapsed;
-            debug!("Rate limiting: sleeping for {:?} before next API call", sleep_duration);
-            tokio::time::sleep(sleep_duration).await;
+    /// Backfill historical candles for `symbol` over `[start_time, end_time)` (ms).
+    ///
+    /// Only base 1-minute candles are fetched from the API, paged to respect
+    /// the exchange's per-request candle cap; every other requested
+    /// resolution is derived in-process from those 1m candles. Persists with
+    /// upsert semantics into a per-resolution Redis hash so re-running a
+    /// backfill (e.g. after a restart) re-derives rather than duplicates.
+    pub async fn backfill_candles(
+        &mut self,
+        symbol: &str,
+        start_time: u64,
+        end_time: u64,
+        resolutions: &[Resolution],
+    ) -> Result<(), ScannerError> {
+        info!("Backfilling {} candles for {} from {} to {}", symbol, resolutions.len(), start_time, end_time);
+
+        // Step 1: fetch base 1m candles across the whole range, paged so we
+        // never ask for more than the exchange's per-request candle cap
+        let mut base_candles: Vec<CandleData> = Vec::new();
+        for (window_start, window_end) in candle_backfill::paged_windows(start_time, end_time) {
+            let page = self.get_candles(
+                symbol.to_string(),
+                Resolution::OneMinute.as_str().to_string(),
+                window_start,
+                window_end,
+            ).await?;
+
+            base_candles.extend(page.iter().map(|c| candle_response_to_data(c)));
         }
-        
-        // Update the last API call timestamp
-        self.last_api_call = Instant::now();
+
+        info!("Fetched {} base 1m candles for {}", base_candles.len(), symbol);
+
+        // Step 2: aggregate upward into every requested resolution and
+        // persist with upsert semantics
+        for resolution in resolutions {
+            let candles = if *resolution == Resolution::OneMinute {
+                base_candles.clone()
+            } else {
+                candle_backfill::aggregate_candles(&base_candles, *resolution)
+            };
+
+            self.persist_candles(symbol, *resolution, &candles).await?;
+        }
+
+        Ok(())
     }
-    
+
+    /// Upsert a set of already-aggregated candles into the per-resolution Redis hash
+    async fn persist_candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        candles: &[CandleData],
+    ) -> Result<(), ScannerError> {
+        let mut conn = self.redis_pool.get().await?;
+        let hash_key = format!("symbol_candles:{}", resolution.as_str());
+
+        for candle in candles {
+            // Field is the bucket start so re-running the backfill overwrites
+            // the same bucket instead of appending a duplicate
+            let field = format!("{}:{}", symbol, candle.time_open);
+            let candle_json = serde_json::to_string(candle)?;
+            conn.hset::<_, _, _, ()>(&hash_key, field, candle_json).await?;
+        }
+
+        info!("Upserted {} {} candles for {} into {}", candles.len(), resolution.as_str(), symbol, hash_key);
+
+        Ok(())
+    }
+
     /// Rate-limited meta call
-    async fn get_meta(&mut self) -> Result<Meta, Box<dyn std::error::Error + Send + Sync>> {
-        self.apply_rate_limit().await;
-        self.info_client.meta().await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    async fn get_meta(&self) -> Result<Meta, ScannerError> {
+        self.rate_limiter.acquire().await;
+        metrics::API_CALLS_TOTAL.with_label_values(&["meta"]).inc();
+        Ok(self.info_client.meta().await?)
     }
-    
+
     /// Rate-limited all_mids call
-    async fn get_all_mids(&mut self) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
-        self.apply_rate_limit().await;
-        self.info_client.all_mids().await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    async fn get_all_mids(&self) -> Result<HashMap<String, String>, ScannerError> {
+        self.rate_limiter.acquire().await;
+        metrics::API_CALLS_TOTAL.with_label_values(&["all_mids"]).inc();
+        Ok(self.info_client.all_mids().await?)
     }
-    
+
     /// Rate-limited candles call
-    async fn get_candles(&mut self, symbol: String, interval: String, start_time: u64, end_time: u64) 
-        -> Result<Vec<CandlesSnapshotResponse>, Box<dyn std::error::Error + Send + Sync>> {
-        self.apply_rate_limit().await;
-        self.info_client.candles_snapshot(symbol, interval, start_time, end_time).await
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    async fn get_candles(&self, symbol: String, interval: String, start_time: u64, end_time: u64)
+        -> Result<Vec<CandlesSnapshotResponse>, ScannerError> {
+        self.rate_limiter.acquire().await;
+        metrics::API_CALLS_TOTAL.with_label_values(&["candles"]).inc();
+        Ok(self.info_client.candles_snapshot(symbol, interval, start_time, end_time).await?)
     }
     
     /// Main method to scan for symbol metrics using REST APIs
-    async fn scan_symbols(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///
+    /// The whole universe is scanned concurrently, bounded by `scan_concurrency`
+    /// in-flight candle requests, while `rate_limiter` caps the aggregate
+    /// request rate against the API regardless of how much concurrency runs
+    /// on top of it.
+    async fn scan_symbols(&self) -> Result<(), ScannerError> {
+        let scan_timer = metrics::SCAN_DURATION_SECONDS.start_timer();
+
         // Step 1: Get metadata for all available assets with rate limiting
         let meta = self.get_meta().await?;
-        
+
         // Step 2: Get all mid prices with rate limiting
         let all_mids = self.get_all_mids().await?;
-        
-        // Extract symbols from meta
-        let mut symbol_metrics = HashMap::new();
-        let mut candle_map = HashMap::new();  // For storing complete candle data
+
         let timestamp = current_timestamp_ms();
-        
-        // Process each asset
-        let universe = &meta.universe;
-        for asset_info in universe[0..1].iter() {
-            // Get symbol name
-            let symbol = asset_info.name.clone();
-            
-            // Find mid price for this symbol
-            let price = match all_mids.get(&symbol) {
-                Some(mid_price_str) => mid_price_str.parse::<f64>().unwrap_or(0.0),
-                None => {
-                    warn!("No price data available for {}, skipping", symbol);
-                    continue;
-                }
-            };
-            
-            // Calculate time range for the 24h candle (now - 24 hours to now)
-            let now = current_timestamp_ms();
-            let twenty_four_hours_ago = now - (24 * 60 * 60 * 1000); // 24 hours in milliseconds
-            
-            // Initialize volume
-            let mut volume_24h = 0.0;
-            let mut latest_candle: Option<CandleData> = None;
-            
-            // Try to get 24h candle data with rate limiting
-            let candles = self.get_candles(
-                symbol.clone(),
-                "1d".to_string(),  // Daily candles
-                twenty_four_hours_ago,
-                now
-            ).await;
-            
-            match candles {
-                Ok(candle_data) => {
-                    if !candle_data.is_empty() {
-                        // Sum up the volume from all candles in the period
-                        for (i, candle) in candle_data.iter().enumerate() {
-                            if let Ok(candle_volume) = candle.vlm.parse::<f64>() {
-                                volume_24h += candle_volume * price;
-                            }
-                            
-                            // Save the most recent candle data
-                            if i == candle_data.len() - 1 {
-                                latest_candle = Some(CandleData {
-                                    time_open: candle.time_open,
-                                    time_close: candle.time_close,
-                                    coin: candle.coin.clone(),
-                                    candle_interval: candle.candle_interval.clone(),
-                                    open: candle.open.clone(),
-                                    close: candle.close.clone(),
-                                    high: candle.high.clone(),
-                                    low: candle.low.clone(),
-                                    vlm: candle.vlm.clone(), // in base currency
-                                    num_trades: candle.num_trades,
-                                    volume_24h: volume_24h, 
-                                    last_updated: timestamp,
-                                    price: price,
-                                });
+        let now = timestamp;
+        let twenty_four_hours_ago = now.saturating_sub(24 * 60 * 60 * 1000);
+
+        // Track which symbols are still listed so stale candle data can be reconciled away
+        let live_symbols: Vec<String> = meta.universe.iter().map(|asset| asset.name.clone()).collect();
+
+        let info_client = self.info_client.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let scan_concurrency = self.scan_concurrency;
+
+        // Fan out a candle fetch per symbol, bounded to `scan_concurrency`
+        // in-flight requests; each fetch still waits on the shared token
+        // bucket, so total API pressure stays bounded no matter the fan-out.
+        let results: Vec<(String, SymbolMetrics, Option<CandleData>)> = stream::iter(meta.universe.into_iter())
+            .map(|asset_info| {
+                let info_client = info_client.clone();
+                let rate_limiter = rate_limiter.clone();
+                let all_mids = &all_mids;
+                async move {
+                    let symbol = asset_info.name.clone();
+
+                    let price = match all_mids.get(&symbol) {
+                        Some(mid_price_str) => mid_price_str.parse::<f64>().unwrap_or(0.0),
+                        None => {
+                            warn!("No price data available for {}, skipping", symbol);
+                            return None;
+                        }
+                    };
+
+                    let mut volume_24h = 0.0;
+                    let mut latest_candle: Option<CandleData> = None;
+
+                    rate_limiter.acquire().await;
+                    metrics::API_CALLS_TOTAL.with_label_values(&["candles"]).inc();
+                    let candles = info_client.candles_snapshot(
+                        symbol.clone(),
+                        "1d".to_string(), // Daily candles
+                        twenty_four_hours_ago,
+                        now,
+                    ).await;
+
+                    match candles {
+                        Ok(candle_data) => {
+                            if !candle_data.is_empty() {
+                                // Sum up the volume from all candles in the period
+                                for (i, candle) in candle_data.iter().enumerate() {
+                                    if let Ok(candle_volume) = candle.vlm.parse::<f64>() {
+                                        volume_24h += candle_volume * price;
+                                    }
+
+                                    // Save the most recent candle data
+                                    if i == candle_data.len() - 1 {
+                                        latest_candle = Some(CandleData {
+                                            time_open: candle.time_open,
+                                            time_close: candle.time_close,
+                                            coin: candle.coin.clone(),
+                                            candle_interval: candle.candle_interval.clone(),
+                                            open: candle.open.clone(),
+                                            close: candle.close.clone(),
+                                            high: candle.high.clone(),
+                                            low: candle.low.clone(),
+                                            vlm: candle.vlm.clone(), // in base currency
+                                            num_trades: candle.num_trades,
+                                            volume_24h,
+                                            last_updated: timestamp,
+                                            price,
+                                        });
+                                    }
+                                }
+                                info!("Found 24h volume for {}: ${:.2}", symbol, volume_24h);
                             }
+                        },
+                        Err(e) => {
+                            warn!("Could not get candle data for {}: {}", symbol, e);
                         }
-                        info!("Found 24h volume for {}: ${:.2}", symbol, volume_24h);
                     }
-                },
-                Err(e) => {
-                    warn!("Could not get candle data for {}: {}", symbol, e);
+
+                    info!("Processed symbol: {} - Price: {}, Volume 24h: {}",
+                        symbol, price, volume_24h);
+
+                    let metrics = SymbolMetrics {
+                        symbol: symbol.clone(),
+                        volume_24h,
+                        is_active: true,
+                        last_updated: timestamp,
+                    };
+
+                    Some((symbol, metrics, latest_candle))
                 }
-            }
-            
-            info!("Processed symbol: {} - Price: {}, Volume 24h: {}", 
-                symbol, price, volume_24h);
-            
-            // Create metrics with information we have
-            let metrics = SymbolMetrics {
-                symbol: symbol.clone(),
-                volume_24h,
-                is_active: true,
-                last_updated: timestamp,
-            };
-            
-            // Insert into metrics map
+            })
+            .buffer_unordered(scan_concurrency)
+            .filter_map(|item| async move { item })
+            .collect()
+            .await;
+
+        let mut symbol_metrics = HashMap::new();
+        let mut candle_map = HashMap::new();
+
+        for (symbol, metrics, latest_candle) in results {
             symbol_metrics.insert(symbol.clone(), metrics);
-            
-            // Add the candle data to the candle map if available
             if let Some(candle) = latest_candle {
                 candle_map.insert(symbol, candle);
             }
         }
-        
+
+        metrics::SYMBOLS_TRACKED.set(symbol_metrics.len() as i64);
+
         // Update shared state with all symbols
         {
             let mut metrics_write = self.metrics.write().await;
             *metrics_write = symbol_metrics.clone();
         }
-        
+
         // Update Redis with both metrics and complete candle data
-        update_redis_with_candles(&self.redis_client, &symbol_metrics, &candle_map, self.top_n_symbols).await?;
-        
+        let redis_result = update_redis_with_candles(
+            &self.redis_pool,
+            &symbol_metrics,
+            &candle_map,
+            self.top_n_symbols,
+            &live_symbols,
+            self.candle_ttl_secs,
+            self.top_symbols_ttl_secs,
+        ).await;
+
+        scan_timer.observe_duration();
+
+        if redis_result.is_err() {
+            metrics::REDIS_WRITE_ERRORS_TOTAL.inc();
+        }
+        redis_result?;
+
         Ok(())
     }
 }
 
 /// Update Redis with symbol metrics and complete candle data
 async fn update_redis_with_candles(
-    redis_client: &RedisClient,
+    redis_pool: &RedisPool,
     symbol_metrics: &HashMap<String, SymbolMetrics>,
     candle_map: &HashMap<String, CandleData>,
-    top_n_symbols: usize
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut conn = redis_client.get_async_connection().await?;
-    
+    top_n_symbols: usize,
+    live_symbols: &[String],
+    candle_ttl_secs: usize,
+    top_symbols_ttl_secs: usize,
+) -> Result<(), ScannerError> {
+    let mut conn = redis_pool.get().await?;
+
     // Sort symbols by volume and take the top N
     let mut symbols: Vec<_> = symbol_metrics.values().cloned().collect();
     symbols.sort_by(|a, b| b.volume_24h.partial_cmp(&a.volume_24h).unwrap_or(std::cmp::Ordering::Equal));
     let top_symbols: Vec<_> = symbols.into_iter().take(top_n_symbols).collect();
-    
-    // Log the top symbols
+
+    // Log the top symbols and publish their volume as a gauge
     info!("Top {} symbols by volume:", top_symbols.len());
     for (idx, symbol) in top_symbols.iter().enumerate() {
-        info!("  {}. {} - Volume: ${:.2}", 
+        info!("  {}. {} - Volume: ${:.2}",
             idx + 1, symbol.symbol, symbol.volume_24h);
+        metrics::SYMBOL_VOLUME_24H.with_label_values(&[&symbol.symbol]).set(symbol.volume_24h);
     }
-    
-    // Store top symbols in Redis for quick access
+
+    // Store top symbols in Redis for quick access, with its own TTL so a crashed
+    // scanner doesn't serve hours-old leaders forever
     let top_symbols_json = serde_json::to_string(&top_symbols)?;
-    conn.set::<_, _, ()>("top_symbols", &top_symbols_json).await?;
-    
-    // Update each symbol's candle data in the Redis hash
+    conn.set_ex::<_, _, ()>("top_symbols", &top_symbols_json, top_symbols_ttl_secs as u64).await?;
+
+    // Update each symbol's candle data, both in the aggregate hash (kept for
+    // callers that want to read every tracked symbol at once) and under a
+    // per-symbol key so each candle can expire independently
     const HASH_KEY: &str = "symbol_candles";
-    
-    // For each new candle, update or add it to the Redis hash
+
     for (symbol, candle) in candle_map {
-        // Serialize the candle data
         let candle_json = serde_json::to_string(candle)?;
-        
-        // Store it in the hash with the symbol as the field name
-        conn.hset::<_, _, _, ()>(HASH_KEY, symbol.clone(), candle_json).await?;
+
+        conn.hset::<_, _, _, ()>(HASH_KEY, symbol.clone(), &candle_json).await?;
+
+        let per_symbol_key = format!("symbol_candle:{}", symbol);
+        conn.set_ex::<_, _, ()>(&per_symbol_key, &candle_json, candle_ttl_secs as u64).await?;
     }
-    
+
+    // Reconciliation: drop hash fields for symbols that are no longer listed
+    // so delisted/illiquid assets don't linger in the aggregate view forever
+    let tracked_fields: Vec<String> = conn.hkeys(HASH_KEY).await?;
+    let live_set: std::collections::HashSet<&String> = live_symbols.iter().collect();
+    let stale_fields: Vec<String> = tracked_fields.into_iter()
+        .filter(|field| !live_set.contains(field))
+        .collect();
+
+    if !stale_fields.is_empty() {
+        info!("Reconciling {} delisted symbols out of the symbol_candles hash", stale_fields.len());
+        conn.hdel::<_, _, ()>(HASH_KEY, stale_fields).await?;
+    }
+
     // Get the total number of symbols in the hash for logging
     let hash_size: usize = conn.hlen(HASH_KEY).await?;
-    
-    info!("Updated metrics and candle data for {} symbols, stored {} total symbols in Redis hash", 
+
+    info!("Updated metrics and candle data for {} symbols, stored {} total symbols in Redis hash",
         candle_map.len(), hash_size);
-    
+
     Ok(())
 }
 
 /// Get current timestamp in milliseconds
 fn current_timestamp_ms() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    
+
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or(Duration::from_secs(0))
         .as_millis() as u64
-} 
\ No newline at end of file
+}
+
+/// Convert a raw API candle response into our persisted `CandleData` shape
+fn candle_response_to_data(candle: &CandlesSnapshotResponse) -> CandleData {
+    let price = candle.close.parse::<f64>().unwrap_or(0.0);
+
+    CandleData {
+        time_open: candle.time_open,
+        time_close: candle.time_close,
+        coin: candle.coin.clone(),
+        candle_interval: candle.candle_interval.clone(),
+        open: candle.open.clone(),
+        close: candle.close.clone(),
+        high: candle.high.clone(),
+        low: candle.low.clone(),
+        vlm: candle.vlm.clone(),
+        num_trades: candle.num_trades,
+        volume_24h: 0.0,
+        last_updated: current_timestamp_ms(),
+        price,
+    }
+}
\ No newline at end of file